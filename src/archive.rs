@@ -0,0 +1,155 @@
+use std::{
+    fmt, fs, io,
+    path::{Path, PathBuf},
+};
+
+use crate::game::Level;
+
+#[derive(Debug)]
+pub enum LoadLevelError {
+    FileError(io::Error),
+    ParseError(serde_json::Error),
+}
+
+impl fmt::Display for LoadLevelError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LoadLevelError::FileError(error) => write!(f, "could not read level: {}", error),
+            LoadLevelError::ParseError(error) => write!(f, "invalid level JSON: {}", error),
+        }
+    }
+}
+
+impl std::error::Error for LoadLevelError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            LoadLevelError::FileError(error) => Some(error),
+            LoadLevelError::ParseError(error) => Some(error),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum SaveLevelError {
+    SerializeError(serde_json::Error),
+    FileError(io::Error),
+}
+
+/// Reads a level back from the JSON it was saved as.
+pub fn load_level(path: impl AsRef<Path>) -> Result<Level, LoadLevelError> {
+    fs::read_to_string(path)
+        .map_err(LoadLevelError::FileError)
+        .and_then(|raw| serde_json::from_str(&raw).map_err(LoadLevelError::ParseError))
+}
+
+/// A folder of levels on disk, where each level is stored as `<name>.json`.
+/// Levels are named by their date, see `level_name`.
+pub struct LevelArchive {
+    dir: PathBuf,
+}
+
+impl LevelArchive {
+    /// Opens the archive at the supplied folder, creating it if it doesn't exist.
+    pub fn open(dir: impl AsRef<Path>) -> io::Result<Self> {
+        let dir = dir.as_ref().to_path_buf();
+        fs::create_dir_all(&dir)?;
+        Ok(Self { dir })
+    }
+
+    /// Returns the path a level with the given name is stored at.
+    pub fn path(&self, name: &str) -> PathBuf {
+        self.dir.join(format!("{}.json", name))
+    }
+
+    /// Returns true if a level with the given name has already been saved.
+    pub fn contains(&self, name: &str) -> bool {
+        self.path(name).is_file()
+    }
+
+    /// Returns the names of every level in the archive, in ascending order, which
+    /// for date names is chronological.
+    pub fn names(&self) -> io::Result<Vec<String>> {
+        let mut names = Vec::new();
+        for entry in fs::read_dir(&self.dir)? {
+            let path = entry?.path();
+            if path.extension().is_some_and(|ext| ext == "json")
+                && let Some(name) = path.file_stem().and_then(|stem| stem.to_str())
+                && !name.starts_with('.')
+            {
+                names.push(name.to_string());
+            }
+        }
+        names.sort();
+        Ok(names)
+    }
+
+    /// Reads the level saved under the given name.
+    pub fn read(&self, name: &str) -> Result<Level, LoadLevelError> {
+        load_level(self.path(name))
+    }
+
+    /// Saves the level under the given name, returning the path it was written to.
+    ///
+    /// The level is written to a temporary file first and then renamed into place,
+    /// so an interrupted run never leaves a partially written level behind.
+    pub fn write(&self, name: &str, level: &Level) -> Result<PathBuf, SaveLevelError> {
+        let raw = serde_json::to_string(level).map_err(SaveLevelError::SerializeError)?;
+        let path = self.path(name);
+        let tmp_path = self.dir.join(format!(".{}.json.tmp", name));
+
+        fs::write(&tmp_path, raw).map_err(SaveLevelError::FileError)?;
+        fs::rename(&tmp_path, &path).map_err(|e| {
+            let _ = fs::remove_file(&tmp_path);
+            SaveLevelError::FileError(e)
+        })?;
+        Ok(path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{game::Grid, position::Position};
+    use std::process;
+
+    fn level() -> Level {
+        Level {
+            start: Position::new(0, 0),
+            goal: Position::new(0, 2),
+            words: vec!["cat".to_string()],
+            grid: Grid::new(1, 3),
+            path: vec![Position::new(0, 0), Position::new(0, 2)],
+            seed: 7,
+            tiles: None,
+        }
+    }
+
+    #[test]
+    fn saved_levels_are_found_and_read_back() {
+        let dir = std::env::temp_dir().join(format!("wc-rs-archive-{}", process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let archive = LevelArchive::open(dir.join("levels")).unwrap();
+        assert!(!archive.contains("2025-01-01"));
+
+        let path = archive.write("2025-01-01", &level()).unwrap();
+        assert_eq!(path, archive.path("2025-01-01"));
+        assert!(archive.contains("2025-01-01"));
+        assert_eq!(archive.read("2025-01-01").unwrap(), level());
+
+        // Only the level itself is left behind, without its temporary file.
+        let files: Vec<_> = fs::read_dir(&archive.dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name())
+            .collect();
+        assert_eq!(files, vec!["2025-01-01.json"]);
+
+        fs::write(archive.dir.join(".2025-01-02.json.tmp"), "{").unwrap();
+        fs::write(archive.dir.join(".hidden.json"), "{").unwrap();
+        fs::write(archive.dir.join("notes.txt"), "").unwrap();
+        archive.write("2024-12-31", &level()).unwrap();
+        assert_eq!(archive.names().unwrap(), vec!["2024-12-31", "2025-01-01"]);
+        assert!(!archive.contains("2025-01-02"));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
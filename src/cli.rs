@@ -0,0 +1,286 @@
+use std::path::PathBuf;
+
+use chrono::NaiveDate;
+use clap::{Args, Parser, Subcommand};
+use wc_rs::{
+    generator::HistoryMode,
+    language::{Language, LetterFrequencies},
+    mask::Shape,
+    solver::{SolverStrategy, WordWeighting},
+    walls::{Symmetry, WallStyle},
+};
+
+/// Generates and inspects levels for wordcrossing.
+#[derive(Parser)]
+#[command(version, about)]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Command,
+}
+
+#[derive(Subcommand)]
+pub enum Command {
+    /// Generate a batch of levels, visualising them and optionally saving them to disk.
+    Generate(Box<GenerateArgs>),
+    /// Visualise previously saved levels.
+    Show(ShowArgs),
+    /// Check a player's solution to a level against the rules of the game.
+    Validate(ValidateArgs),
+    /// Print statistics about a word list.
+    Stats(StatsArgs),
+    /// Find the words in a word list matching a pattern.
+    Words(WordsArgs),
+}
+
+/// The word lists a subcommand reads, and the language they are in.
+#[derive(Args)]
+pub struct WordListArgs {
+    /// Word lists to use, merged together. Accepts JSON lists or text files with a
+    /// word per line, optionally gzip compressed.
+    #[arg(short, long, default_value = "assets/easy_words.json")]
+    pub words: Vec<PathBuf>,
+
+    /// Language of the word lists, which decides the letters words may contain.
+    #[arg(long, value_enum, default_value_t = Language::English)]
+    pub language: Language,
+}
+
+#[derive(Args)]
+#[command(mut_arg("words", |arg| arg
+    .help("Word lists used to build solutions, merged together")))]
+pub struct GenerateArgs {
+    /// Number of levels to generate.
+    #[arg(short = 'n', long, default_value_t = 10)]
+    pub count: usize,
+
+    /// Number of rows in each level's grid.
+    #[arg(long, default_value_t = 8)]
+    pub rows: usize,
+
+    /// Number of columns in each level's grid.
+    #[arg(long, default_value_t = 8)]
+    pub cols: usize,
+
+    /// Outline of each level's board, drawn to fit `--rows` and `--cols`.
+    #[arg(long, conflicts_with = "mask")]
+    pub shape: Option<Shape>,
+
+    /// File with the outline of each level's board, drawn one row per line, where
+    /// spaces and `~` are outside the board. Overrides `--rows` and `--cols`.
+    #[arg(long)]
+    pub mask: Option<PathBuf>,
+
+    /// Hand drawn board to build every level on, instead of generating walls. See
+    /// the README for the format.
+    #[arg(long)]
+    pub template: Option<PathBuf>,
+
+    /// How walls are placed on each level's grid.
+    #[arg(long, value_enum, default_value_t = WallStyle::Noise)]
+    pub walls: WallStyle,
+
+    /// Symmetry of each level's walls, like a published crossword.
+    #[arg(long, value_enum, default_value_t = Symmetry::None)]
+    pub symmetry: Symmetry,
+
+    /// Least share of the board, from 0 to 1, that each level's room must cover.
+    /// Grids with less free space are regenerated.
+    #[arg(long, default_value_t = 0.0)]
+    pub min_free_fraction: f64,
+
+    /// Fewest squares each level's room may have. Smaller rooms are regenerated.
+    #[arg(long, default_value_t = 0)]
+    pub min_room_size: usize,
+
+    /// Most squares a one-wide corridor may run for, bends included. Grids with
+    /// longer corridors are regenerated.
+    #[arg(long)]
+    pub max_corridor: Option<usize>,
+
+    /// Wall off single squares poking out of each level's room.
+    #[arg(long)]
+    pub fill_pockets: bool,
+
+    #[command(flatten)]
+    pub word_list: WordListArgs,
+
+    /// Word lists players are allowed to use, merged together. Words used to build
+    /// solutions must also be in these lists.
+    #[arg(long, default_value = "assets/words.json")]
+    pub play_words: Vec<PathBuf>,
+
+    /// Where the extra letters added to each level's letters are sampled from.
+    #[arg(long, value_enum, default_value_t = LetterFrequencies::WordList)]
+    pub letter_frequencies: LetterFrequencies,
+
+    /// How common each word is, as a text file with a word and a tab separated count
+    /// on each line, or just the words from most to least common. Used by
+    /// `--weighting familiar` and the rank limits.
+    #[arg(long)]
+    pub frequencies: Option<PathBuf>,
+
+    /// Only build solutions from words at least this common, where rank 1 is the
+    /// most common word.
+    #[arg(long, requires = "frequencies", conflicts_with = "max_rank_by_weekday")]
+    pub max_rank: Option<usize>,
+
+    /// The `--max-rank` of each day of the week from Monday to Sunday, separated by
+    /// commas, so early week levels can use everyday words while later ones allow
+    /// rarer words. A rank of 0 doesn't limit that day.
+    #[arg(
+        long,
+        requires = "frequencies",
+        value_delimiter = ',',
+        value_name = "RANKS"
+    )]
+    pub max_rank_by_weekday: Vec<usize>,
+
+    /// Word lists of words that must never appear in a level, removed from both the
+    /// generation and play word lists.
+    #[arg(long)]
+    pub blocklist: Vec<PathBuf>,
+
+    /// Number of previous days whose solution words are avoided, read from the
+    /// output folder and the levels generated earlier in the run.
+    #[arg(long, default_value_t = 0)]
+    pub history: usize,
+
+    /// How the solution words of recent days are avoided.
+    #[arg(long, value_enum, default_value_t = HistoryMode::Exclude)]
+    pub history_mode: HistoryMode,
+
+    /// How likely a recent solution word is to be chosen relative to other words,
+    /// with `--history-mode down-weight`.
+    #[arg(long, default_value_t = 0.1)]
+    pub history_weight: f64,
+
+    /// Folder to save the levels to, as `YYYY-MM-DD.json`. The folder is created if
+    /// needed, and dates that already have a level are skipped, so a failed run can be
+    /// resumed. Levels are visualised either way, and only saved if this is given.
+    #[arg(short, long)]
+    pub output: Option<PathBuf>,
+
+    /// Date of the first level in YYYY-MM-DD format. Defaults to today.
+    #[arg(long)]
+    pub start_date: Option<NaiveDate>,
+
+    /// Algorithm used to find a solution for each level.
+    #[arg(long, value_enum, default_value_t = SolverStrategy::Backtracking)]
+    pub solver: SolverStrategy,
+
+    /// How the random and backtracking solvers weigh candidate words.
+    #[arg(long, value_enum, default_value_t = WordWeighting::Permissive)]
+    pub weighting: WordWeighting,
+
+    /// How many times the random solver may retry a level before it is discarded.
+    #[arg(long, default_value_t = 20)]
+    pub solver_retries: usize,
+
+    /// How many words the backtracking solver may try placing before a level is
+    /// discarded.
+    #[arg(long, default_value_t = 10000)]
+    pub node_budget: usize,
+
+    /// How many more turns than the minimum a solution's path may take, when the
+    /// minimum-turn paths can't be solved.
+    #[arg(long, default_value_t = 2)]
+    pub extra_turns: usize,
+
+    /// How many more squares than the shortest path a solution's path may cover.
+    #[arg(long, default_value_t = 2)]
+    pub extra_length: usize,
+
+    /// The most paths between the start and goal to try solving before a level is
+    /// discarded.
+    #[arg(long, default_value_t = 20)]
+    pub max_paths: usize,
+
+    /// Most candidate levels to try for each level before giving up.
+    #[arg(long, default_value_t = 10000)]
+    pub max_candidates: u64,
+
+    /// Number of each solution's letters placed on the grid as locked tiles, which
+    /// players start with and can build words through.
+    #[arg(long, default_value_t = 0)]
+    pub locked_letters: usize,
+
+    /// Minimum average word length of a level's solution.
+    #[arg(long, default_value_t = 4)]
+    pub min_avg_word_length: usize,
+
+    /// Seed for the batch. The level at index `i` is generated from `seed + i`, and
+    /// records that seed in its JSON. A random seed is chosen if neither this nor
+    /// `--salt` are supplied.
+    #[arg(long, conflicts_with = "salt")]
+    pub seed: Option<u64>,
+
+    /// Number of worker threads generating candidate levels. Defaults to the number
+    /// of available CPU cores.
+    #[arg(short = 'j', long)]
+    pub threads: Option<usize>,
+
+    /// Secret salt to derive each level's seed from its date, so any day's level can
+    /// be regenerated on its own with the same salt.
+    #[arg(long)]
+    pub salt: Option<String>,
+}
+
+#[derive(Args)]
+pub struct StatsArgs {
+    #[command(flatten)]
+    pub word_list: WordListArgs,
+}
+
+#[derive(Args)]
+pub struct ShowArgs {
+    /// Level files, or folders of levels, to visualise.
+    #[arg(required = true)]
+    pub paths: Vec<PathBuf>,
+}
+
+#[derive(Args)]
+#[command(mut_arg("words", |arg| arg
+    .default_value("assets/words.json")
+    .help("Word lists players are allowed to use, merged together")))]
+pub struct ValidateArgs {
+    /// The level the solution is for.
+    #[arg(short, long)]
+    pub level: PathBuf,
+
+    #[command(flatten)]
+    pub word_list: WordListArgs,
+
+    /// JSON file of the placed words, as a list of
+    /// `{"word": "cat", "start": {"row": 0, "col": 1}, "orientation": "across"}`.
+    pub solution: PathBuf,
+}
+
+#[derive(Args)]
+pub struct WordsArgs {
+    /// Pattern the words must match, one square at a time: `?` for any letter, a
+    /// letter for itself, or `[abc]` for any of the letters. For example `?a??s`.
+    pub pattern: Option<String>,
+
+    /// Length of the words, when no pattern is given.
+    #[arg(short, long, conflicts_with = "pattern")]
+    pub length: Option<usize>,
+
+    /// Letters the words must contain in a row, anywhere in the word. This is how
+    /// patterns are combined with letters that aren't tied to a square. Can be
+    /// passed several times.
+    #[arg(short, long)]
+    pub contains: Vec<String>,
+
+    /// Only find words that can be spelled from these letter tiles, each used at
+    /// most once. Letters given in the pattern are taken to be on the board already,
+    /// so don't need a tile. Accents are folded as in the word list.
+    #[arg(long)]
+    pub letters: Option<String>,
+
+    /// The most matching words to print.
+    #[arg(long, default_value_t = 50)]
+    pub limit: usize,
+
+    #[command(flatten)]
+    pub word_list: WordListArgs,
+}
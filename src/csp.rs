@@ -0,0 +1,280 @@
+use rand::Rng;
+use std::collections::{HashSet, VecDeque};
+
+use crate::{
+    position::Position,
+    solver::{SolveOutcome, WordConstraint, WordList, segment_positions, weighted_order},
+};
+
+/// A shared-letter constraint between two segments that cross the same square:
+/// the letter at `from_index` in the word of segment `from` must appear at
+/// `to_index` in the word of segment `to`. Every constraint is stored in both
+/// directions.
+struct Arc {
+    from: usize,
+    from_index: usize,
+    to: usize,
+    to_index: usize,
+}
+
+/// Solves a chain of segments as a constraint satisfaction problem.
+///
+/// Each segment is a variable whose domain starts as the words matching its
+/// constraints. Shared-letter constraints between segments are kept arc consistent
+/// throughout a backtracking search, which always branches on the segment with
+/// the fewest remaining words, trying its words in an order biased by their
+/// weights in the word list.
+///
+/// Returns the word for each segment, or why no words were found. The search gives
+/// up after assigning `node_budget` words.
+pub fn solve_segments<'a, R: Rng + ?Sized>(
+    segments: &[(Position, Position)],
+    constraints: Vec<Vec<WordConstraint>>,
+    word_list: &'a WordList,
+    node_budget: usize,
+    rng: &mut R,
+) -> Result<Vec<&'a str>, SolveOutcome> {
+    let arcs = find_arcs(segments);
+    let mut domains: Vec<Vec<&str>> = constraints
+        .iter()
+        .map(|constraints| word_list.find_constrained_words(constraints))
+        .collect();
+
+    // A segment that crosses no others has no arcs to empty its domain, so
+    // propagation can't be relied on to notice it has no words.
+    if domains.iter().any(|domain| domain.is_empty())
+        || !propagate(&mut domains, &arcs, (0..arcs.len()).collect())
+    {
+        return Err(SolveOutcome::Unsolvable);
+    }
+    let mut nodes = 0;
+    let domains = search(domains, &arcs, word_list, &mut nodes, node_budget, rng)?;
+    Ok(domains.into_iter().map(|domain| domain[0]).collect())
+}
+
+fn search<'a, R: Rng + ?Sized>(
+    domains: Vec<Vec<&'a str>>,
+    arcs: &[Arc],
+    word_list: &WordList,
+    nodes: &mut usize,
+    node_budget: usize,
+    rng: &mut R,
+) -> Result<Vec<Vec<&'a str>>, SolveOutcome> {
+    // Once every domain has a single word, arc consistency means every
+    // constraint holds.
+    let Some(variable) = (0..domains.len())
+        .filter(|&i| domains[i].len() > 1)
+        .min_by_key(|&i| domains[i].len())
+    else {
+        return Ok(domains);
+    };
+
+    let values = domains[variable].clone();
+    let weights: Vec<f64> = values.iter().map(|word| word_list.weight(word)).collect();
+    for value in weighted_order(values, &weights, rng) {
+        if *nodes >= node_budget {
+            return Err(SolveOutcome::GaveUp);
+        }
+        *nodes += 1;
+
+        let mut next = domains.clone();
+        next[variable] = vec![value];
+        let queue = (0..arcs.len())
+            .filter(|&a| arcs[a].to == variable)
+            .collect();
+        if !propagate(&mut next, arcs, queue) {
+            continue;
+        }
+        match search(next, arcs, word_list, nodes, node_budget, rng) {
+            Err(SolveOutcome::Unsolvable) => continue,
+            result => return result,
+        }
+    }
+    Err(SolveOutcome::Unsolvable)
+}
+
+/// Runs AC-3 from the queued arcs, removing words that have no matching word in a
+/// crossing segment. Returns false if a domain is emptied.
+fn propagate(domains: &mut [Vec<&str>], arcs: &[Arc], queue: VecDeque<usize>) -> bool {
+    let mut queue = queue;
+    let mut queued: Vec<bool> = vec![false; arcs.len()];
+    for &a in queue.iter() {
+        queued[a] = true;
+    }
+
+    while let Some(a) = queue.pop_front() {
+        queued[a] = false;
+        let arc = &arcs[a];
+        let supported: HashSet<char> = domains[arc.to]
+            .iter()
+            .filter_map(|word| word.chars().nth(arc.to_index))
+            .collect();
+
+        let before = domains[arc.from].len();
+        domains[arc.from].retain(|word| {
+            word.chars()
+                .nth(arc.from_index)
+                .is_some_and(|letter| supported.contains(&letter))
+        });
+        if domains[arc.from].is_empty() {
+            return false;
+        }
+        if domains[arc.from].len() == before {
+            continue;
+        }
+
+        // Segments crossing the one that just changed need revising.
+        for (b, other) in arcs.iter().enumerate() {
+            if other.to == arc.from && other.from != arc.to && !queued[b] {
+                queued[b] = true;
+                queue.push_back(b);
+            }
+        }
+    }
+    true
+}
+
+/// Finds the squares shared between every pair of segments.
+fn find_arcs(segments: &[(Position, Position)]) -> Vec<Arc> {
+    let cells: Vec<Vec<Position>> = segments.iter().map(|s| segment_positions(*s)).collect();
+    let mut arcs = Vec::new();
+    for (i, a) in cells.iter().enumerate() {
+        for (j, b) in cells.iter().enumerate() {
+            if i == j {
+                continue;
+            }
+            for (from_index, position) in a.iter().enumerate() {
+                if let Some(to_index) = b.iter().position(|p| p == position) {
+                    arcs.push(Arc {
+                        from: i,
+                        from_index,
+                        to: j,
+                        to_index,
+                    });
+                }
+            }
+        }
+    }
+    arcs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::level_rng;
+    use crate::solver::tests::word_list;
+    use std::collections::HashMap;
+
+    fn lengths(segments: &[(Position, Position)]) -> Vec<Vec<WordConstraint>> {
+        segments
+            .iter()
+            .map(|&(start, end)| vec![WordConstraint::Length(start.manhattan_distance(end) + 1)])
+            .collect()
+    }
+
+    /// Four three letter segments, zigzagging right, down, left then down again.
+    fn zigzag() -> Vec<(Position, Position)> {
+        vec![
+            (Position::new(0, 0), Position::new(0, 2)),
+            (Position::new(0, 2), Position::new(2, 2)),
+            (Position::new(2, 2), Position::new(2, 0)),
+            (Position::new(2, 0), Position::new(4, 0)),
+        ]
+    }
+
+    /// Arcs into `variable`, as queued by the search after assigning it.
+    fn arcs_into(arcs: &[Arc], variable: usize) -> VecDeque<usize> {
+        (0..arcs.len())
+            .filter(|&a| arcs[a].to == variable)
+            .collect()
+    }
+
+    #[test]
+    fn propagation_prunes_in_both_directions() {
+        // Two separate chains: "cat tin nap pen" and "dog gum mud den".
+        let arcs = find_arcs(&zigzag());
+        let domains = vec![
+            vec!["cat", "dog"],
+            vec!["tin", "gum"],
+            vec!["nap", "mud"],
+            vec!["pen", "den"],
+        ];
+
+        let mut backwards = domains.clone();
+        backwards[3] = vec!["pen"];
+        assert!(propagate(&mut backwards, &arcs, arcs_into(&arcs, 3)));
+        assert_eq!(
+            backwards,
+            vec![vec!["cat"], vec!["tin"], vec!["nap"], vec!["pen"]]
+        );
+
+        let mut forwards = domains;
+        forwards[0] = vec!["dog"];
+        assert!(propagate(&mut forwards, &arcs, arcs_into(&arcs, 0)));
+        assert_eq!(
+            forwards,
+            vec![vec!["dog"], vec!["gum"], vec!["mud"], vec!["den"]]
+        );
+    }
+
+    #[test]
+    fn propagation_alone_rules_out_contradictions() {
+        // Without a budget for the search, this can only be unsolvable if
+        // propagation finds that no chain of words is four long.
+        let words = word_list(&["cat", "tin", "nap", "gum", "mud"]);
+        let segments = zigzag();
+        let result = solve_segments(&segments, lengths(&segments), &words, 0, &mut level_rng(0));
+        assert_eq!(result, Err(SolveOutcome::Unsolvable));
+    }
+
+    #[test]
+    fn segments_without_words_are_unsolvable() {
+        // A single segment has no arcs for propagation to check.
+        let words = word_list(&["cat", "tin"]);
+        let segments = vec![(Position::new(0, 0), Position::new(0, 11))];
+        let result = solve_segments(
+            &segments,
+            lengths(&segments),
+            &words,
+            1000,
+            &mut level_rng(0),
+        );
+        assert_eq!(result, Err(SolveOutcome::Unsolvable));
+    }
+
+    #[test]
+    fn solved_words_agree_where_segments_cross() {
+        // Right along the third row, up, left, then down through the middle of
+        // the first segment, which only "alas" can do after "cat".
+        let segments = vec![
+            (Position::new(2, 0), Position::new(2, 2)),
+            (Position::new(2, 2), Position::new(0, 2)),
+            (Position::new(0, 2), Position::new(0, 1)),
+            (Position::new(0, 1), Position::new(3, 1)),
+        ];
+        let words = word_list(&[
+            "cat", "cot", "top", "tip", "pa", "pi", "alas", "also", "able",
+        ]);
+        for seed in 0..10 {
+            let solution = solve_segments(
+                &segments,
+                lengths(&segments),
+                &words,
+                1000,
+                &mut level_rng(seed),
+            )
+            .unwrap();
+            assert_eq!(solution.len(), segments.len());
+
+            let mut letters: HashMap<Position, char> = HashMap::new();
+            for (segment, word) in segments.iter().zip(solution) {
+                assert!(words.is_word_valid(word));
+                for (position, letter) in segment_positions(*segment).into_iter().zip(word.chars())
+                {
+                    assert_eq!(*letters.entry(position).or_insert(letter), letter);
+                }
+            }
+            assert_eq!(letters[&Position::new(2, 1)], 'a');
+        }
+    }
+}
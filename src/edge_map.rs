@@ -14,3 +14,9 @@ impl<T> EdgeMap<T> {
         self.0.get(&start)?.get(&end)
     }
 }
+
+impl<T> Default for EdgeMap<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -1,44 +1,106 @@
 use rand::prelude::*;
-use serde::{
-    Deserialize, Serialize, Serializer,
-    ser::{SerializeMap, SerializeStruct},
-};
-use std::collections::{HashMap, HashSet};
+use serde::{Deserialize, Deserializer, Serialize, Serializer, de::Error as _, ser::SerializeMap};
+use std::collections::{HashMap, HashSet, VecDeque, hash_map::Entry};
 
 use crate::{
     DistanceMap, TurnsMap,
     edge_map::EdgeMap,
+    letter_bag::LetterBag,
+    mask::Mask,
     position::{Direction, Position},
+    solver::segment_positions,
+    walls::{Symmetry, WallStrategy},
 };
 
-/// An Entity in Wordcrossing which occupies a square on the grid. In this generator,
-/// only `Entity::Wall` is used.
-#[derive(PartialEq, Eq, Copy, Clone, Serialize, Deserialize)]
-#[serde(tag = "type")]
+/// An Entity in Wordcrossing which occupies a square on the grid. `Entity::Void`
+/// marks squares outside the board's mask, which are not part of the board at all.
+///
+/// Entities are serialized as `{"type": "wall"}`, with letters carrying their
+/// letter as `{"type": "letter", "value": "a"}`.
+#[derive(PartialEq, Eq, Copy, Clone, Debug, Serialize, Deserialize)]
+#[serde(tag = "type", content = "value")]
 #[serde(rename_all = "lowercase")]
 pub enum Entity {
     Wall,
     Letter(char),
     Nothing,
+    Void,
 }
 
 fn serialize_entities<S>(entities: &HashMap<Position, Entity>, s: S) -> Result<S::Ok, S::Error>
 where
     S: Serializer,
 {
+    // Sort the entities so the same grid always serializes to the same JSON.
+    let mut sorted: Vec<_> = entities.iter().collect();
+    sorted.sort_by_key(|(k, _)| **k);
+
     let mut e = s.serialize_map(Some(entities.len()))?;
-    for (k, v) in entities {
+    for (k, v) in sorted {
         e.serialize_entry(&k.to_key(), &v)?;
     }
     e.end()
 }
 
+fn deserialize_entities<'de, D>(d: D) -> Result<HashMap<Position, Entity>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let raw = HashMap::<String, Entity>::deserialize(d)?;
+    raw.into_iter()
+        .map(|(k, v)| match Position::from_key(&k) {
+            Some(position) => Ok((position, v)),
+            None => Err(D::Error::custom(format!("invalid position key {:?}", k))),
+        })
+        .collect()
+}
+
+fn serialize_seed<S>(seed: &u64, s: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    // JavaScript reads JSON numbers as doubles, which can't hold every 64-bit
+    // seed, so it is written as a string.
+    s.serialize_str(&seed.to_string())
+}
+
+fn deserialize_seed<'de, D>(d: D) -> Result<u64, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    // Levels written before seeds were strings hold them as numbers.
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Seed {
+        Number(u64),
+        String(String),
+    }
+
+    match Seed::deserialize(d)? {
+        Seed::Number(seed) => Ok(seed),
+        Seed::String(seed) => seed
+            .parse()
+            .map_err(|_| D::Error::custom(format!("invalid seed {:?}", seed))),
+    }
+}
+
+/// Returns the supplied positions in sorted order, so that algorithms iterating
+/// over them behave the same between runs.
+pub fn sorted_positions(positions: &HashSet<Position>) -> Vec<Position> {
+    let mut sorted: Vec<Position> = positions.iter().copied().collect();
+    sorted.sort();
+    sorted
+}
+
 /// A representation of the grid in Wordcrossing.
-#[derive(PartialEq, Eq, Clone, Serialize)]
+#[derive(PartialEq, Eq, Clone, Debug, Serialize, Deserialize)]
 pub struct Grid {
     pub rows: usize,
     pub cols: usize,
-    #[serde(serialize_with = "serialize_entities")]
+    #[serde(
+        serialize_with = "serialize_entities",
+        deserialize_with = "deserialize_entities"
+    )]
     pub entities: HashMap<Position, Entity>,
 }
 
@@ -52,25 +114,61 @@ impl Grid {
         }
     }
 
+    /// Creates a new grid shaped by the mask, where squares outside the mask are
+    /// void.
+    pub fn from_mask(mask: &Mask) -> Self {
+        let mut grid = Self::new(mask.rows, mask.cols);
+        let void = grid
+            .all_positions()
+            .into_iter()
+            .filter(|p| !mask.contains(*p))
+            .collect();
+        grid.set_positions(void, Entity::Void);
+        grid
+    }
+
     /// Returns all positions contained within this grid, regardless of whether
-    /// or not they have an entity on them.
+    /// or not they have an entity on them, except void squares outside the board.
     pub fn all_positions(&self) -> Vec<Position> {
         let mut result: Vec<Position> = Vec::new();
         for row in 0..self.rows {
             for col in 0..self.cols {
-                result.push(Position::new(row as isize, col as isize))
+                let position = Position::new(row as isize, col as isize);
+                if !self.is_void(position) {
+                    result.push(position)
+                }
             }
         }
         result
     }
 
-    /// Add a series of (position, entity) pairs to the grid.
+    /// Returns true if the position is void, outside the board's mask.
+    pub fn is_void(&self, position: Position) -> bool {
+        self.entities.get(&position) == Some(&Entity::Void)
+    }
+
+    /// Returns true if the position is a wall.
+    pub fn is_wall(&self, position: Position) -> bool {
+        self.entities.get(&position) == Some(&Entity::Wall)
+    }
+
+    /// Add a series of (position, entity) pairs to the grid. Void squares are
+    /// permanent, so are never replaced.
     pub fn add_entities(&mut self, entities: impl Iterator<Item = (Position, Entity)>) {
         entities.for_each(|(pos, entity)| {
-            self.entities.insert(pos, entity);
+            if !self.is_void(pos) {
+                self.entities.insert(pos, entity);
+            }
         });
     }
 
+    /// Removes any entity from the position, unless it is void.
+    pub fn clear(&mut self, position: Position) {
+        if !self.is_void(position) {
+            self.entities.remove(&position);
+        }
+    }
+
     /// Spawns the given entity at all the supplied positions.
     pub fn set_positions(&mut self, positions: Vec<Position>, entity: Entity) {
         self.add_entities(positions.into_iter().map(|p| (p, entity)))
@@ -82,14 +180,13 @@ impl Grid {
     /// `max_area` in 0..1 is the maximum percentage to wall off.
     ///
     /// A random amount between min and max areas will be chosen.
-    pub fn randomise_walls(&mut self, min_area: f32, max_area: f32) {
-        let roll: f32 = rand::random();
+    pub fn randomise_walls<R: Rng + ?Sized>(&mut self, min_area: f32, max_area: f32, rng: &mut R) {
+        let roll: f32 = rng.random();
         let area = min_area + roll * (max_area - min_area);
-        let wall_count = (area * self.rows as f32 * self.cols as f32).round() as usize;
-
-        let mut rng = rand::rng();
         let mut positions = self.all_positions();
-        positions.shuffle(&mut rng);
+        let wall_count = (area * positions.len() as f32).round() as usize;
+
+        positions.shuffle(rng);
         let walls_to_be: Vec<Position> = positions.into_iter().take(wall_count).collect();
         self.set_positions(walls_to_be, Entity::Wall);
     }
@@ -103,21 +200,17 @@ impl Grid {
         let mut grey: HashSet<Position> = HashSet::new();
         let mut queue: Vec<Position> = vec![start];
 
-        if let Some(Entity::Wall) = self.entities.get(&start) {
+        if self.is_wall(start) || self.is_void(start) {
             return visited;
         }
 
-        while !queue.is_empty() {
-            let node = queue.pop().unwrap();
+        while let Some(node) = queue.pop() {
             visited.insert(node);
             for neighbour in self.valid_neighbours(node) {
                 if grey.contains(&neighbour) || visited.contains(&neighbour) {
                     continue;
                 }
-                if let Some(Entity::Wall) = self.entities.get(&neighbour) {
-                    continue;
-                }
-                if !neighbour.is_within_bounds(self.rows as isize, self.cols as isize) {
+                if self.is_wall(neighbour) {
                     continue;
                 }
                 queue.push(neighbour);
@@ -141,32 +234,151 @@ impl Grid {
                 continue;
             }
             result.push(section.clone());
-            seen = seen
-                .union(&section)
-                .into_iter()
-                .map(|p| p.clone())
-                .collect()
+            seen = seen.union(&section).copied().collect()
         }
 
         result
     }
 
-    /// Randomises the walls within this grid, then walls off every section
-    /// except the largest one, to make it clearer to the user where they can
-    /// go.
-    pub fn initialise_walls(&mut self) -> HashSet<Position> {
-        self.randomise_walls(0.15, 0.5);
+    /// Places walls within this grid with the strategy, then walls off every
+    /// section except the largest one, to make it clearer to the user where they
+    /// can go.
+    ///
+    /// With a symmetry, the walls are made symmetric first. The largest section
+    /// is kept along with its mirror images, which are joined to it with symmetric
+    /// corridors if the symmetry split them apart.
+    pub fn initialise_walls<R: Rng + ?Sized>(
+        &mut self,
+        strategy: &dyn WallStrategy,
+        symmetry: Symmetry,
+        mut rng: &mut R,
+    ) -> HashSet<Position> {
+        strategy.place_walls(self, &mut rng);
+        self.symmetrise(symmetry);
+        self.keep_largest_room(symmetry)
+    }
+
+    /// Walls off every section except the largest, keeping the sections holding
+    /// its images under the symmetry and joining them to it with symmetric
+    /// corridors. Returns the free space left, which is a single section.
+    pub fn keep_largest_room(&mut self, symmetry: Symmetry) -> HashSet<Position> {
         let mut sections = self.find_connected_sections();
         // Sort by largest component
         sections.sort_by_key(|section| section.len());
         sections.reverse();
+        if sections.is_empty() {
+            return HashSet::new();
+        }
+
+        // Keep every section holding an image of the largest, so walling off the
+        // rest keeps the grid symmetric.
+        let images: HashSet<Position> = sections[0]
+            .iter()
+            .flat_map(|p| symmetry.images(*p, self.rows, self.cols))
+            .collect();
+        let (kept, unreachable): (Vec<_>, Vec<_>) = sections
+            .into_iter()
+            .partition(|section| !section.is_disjoint(&images));
 
         // Wall off unreachable sections from the largest section.
-        let unreachable: Vec<_> = sections.iter().skip(1).collect();
         for section in unreachable {
-            self.set_positions(section.iter().cloned().collect(), Entity::Wall);
+            self.set_positions(section.into_iter().collect(), Entity::Wall);
+        }
+        if kept.len() > 1 {
+            let anchor = sorted_positions(&kept[0])[0];
+            self.connect_free_space(anchor, symmetry);
+        }
+        self.free_space()
+    }
+
+    /// Makes the walls symmetric by copying the first square of each set of images
+    /// under the symmetry, wall or free, onto the rest. Copying rather than walling
+    /// off every image of every wall keeps the share of walls the strategy placed.
+    pub fn symmetrise(&mut self, symmetry: Symmetry) {
+        for position in self.all_positions() {
+            let images = symmetry.images(position, self.rows, self.cols);
+            let Some(&first) = images.iter().find(|p| !self.is_void(**p)) else {
+                continue;
+            };
+            if first == position {
+                continue;
+            }
+            if self.is_wall(first) {
+                self.set_positions(vec![position], Entity::Wall);
+            } else {
+                self.clear(position);
+            }
         }
-        sections[0].clone()
+    }
+
+    /// Joins the free space into one section by repeatedly carving the shortest
+    /// corridor from the section holding `anchor` to a free square outside it,
+    /// clearing the corridor's images under the symmetry as well. Free space that
+    /// the void cuts off is walled off instead.
+    fn connect_free_space(&mut self, anchor: Position, symmetry: Symmetry) {
+        loop {
+            let connected = self.explore_section(anchor);
+            let rest: HashSet<Position> =
+                self.free_space().difference(&connected).copied().collect();
+            if rest.is_empty() {
+                return;
+            }
+
+            let Some(corridor) = self.shortest_corridor(&connected, &rest) else {
+                self.set_positions(rest.into_iter().collect(), Entity::Wall);
+                return;
+            };
+            let cleared: Vec<Position> = corridor
+                .into_iter()
+                .flat_map(|p| symmetry.images(p, self.rows, self.cols))
+                .collect();
+            for position in cleared {
+                self.clear(position);
+            }
+        }
+    }
+
+    /// Finds the fewest squares, walls or not, joining a square in `from` to a
+    /// square in `to` without crossing the void.
+    fn shortest_corridor(
+        &self,
+        from: &HashSet<Position>,
+        to: &HashSet<Position>,
+    ) -> Option<Vec<Position>> {
+        let mut previous: HashMap<Position, Option<Position>> = HashMap::new();
+        let mut queue: VecDeque<Position> = VecDeque::new();
+        for position in sorted_positions(from) {
+            previous.insert(position, None);
+            queue.push_back(position);
+        }
+
+        while let Some(position) = queue.pop_front() {
+            if to.contains(&position) {
+                let mut corridor = vec![position];
+                while let Some(Some(before)) = previous.get(corridor.last().unwrap()) {
+                    corridor.push(*before);
+                }
+                return Some(corridor);
+            }
+            for neighbour in self.valid_neighbours(position) {
+                if let Entry::Vacant(entry) = previous.entry(neighbour) {
+                    entry.insert(Some(position));
+                    queue.push_back(neighbour);
+                }
+            }
+        }
+        None
+    }
+
+    /// Returns the letters placed on the grid.
+    pub fn letters(&self) -> HashMap<Position, char> {
+        self.entities
+            .iter()
+            .filter_map(|(position, entity)| match entity {
+                Entity::Letter(letter) => Some((*position, *letter)),
+                _ => None,
+            })
+            .collect()
     }
 
     /// Finds the free space within this grid. The free space is the set of
@@ -180,12 +392,13 @@ impl Grid {
     }
 
     /// Returns all valid neighbours of a position, constrained by the dimensions
-    /// of the grid.
+    /// of the grid and its mask.
     pub fn valid_neighbours(&self, position: Position) -> Vec<Position> {
         position
             .neighbours()
             .into_iter()
             .filter(|&p| p.is_within_bounds(self.rows as isize, self.cols as isize))
+            .filter(|&p| !self.is_void(p))
             .collect()
     }
 
@@ -204,11 +417,12 @@ impl Grid {
             result.0.insert(position, payload);
         }
 
+        let positions = sorted_positions(&free_space);
         let mut changed = true;
         while changed {
             changed = false;
-            for position in free_space.iter() {
-                let turns = result.0.get(&position).unwrap();
+            for position in positions.iter() {
+                let turns = result.0.get(position).unwrap();
                 let mut next_turns = turns.clone();
                 for neighbour in self.valid_neighbours(*position) {
                     if !free_space.contains(&neighbour) {
@@ -218,7 +432,7 @@ impl Grid {
                     for (destination, (neighbour_turn_count, direction)) in neighbour_turns {
                         let direction_to_neighbour = position.direction_to_position(neighbour);
                         let mut turn_count = *neighbour_turn_count;
-                        if direction.clone() != direction_to_neighbour {
+                        if *direction != direction_to_neighbour {
                             turn_count += 1;
                         }
 
@@ -250,11 +464,12 @@ impl Grid {
             result.0.insert(position, payload);
         }
 
+        let positions = sorted_positions(&free_space);
         let mut changed = true;
         while changed {
             changed = false;
-            for position in free_space.iter() {
-                let distances = result.0.get(&position).unwrap();
+            for position in positions.iter() {
+                let distances = result.0.get(position).unwrap();
                 let mut next_distances = distances.clone();
                 for neighbour in self.valid_neighbours(*position) {
                     if !free_space.contains(&neighbour) {
@@ -263,9 +478,9 @@ impl Grid {
                     let neighbour_distances = result.0.get(&neighbour).unwrap();
                     for (destination, distance) in neighbour_distances {
                         if !distances.contains_key(destination)
-                            || distance + 1 < distances.get(destination).unwrap().clone()
+                            || distance + 1 < *distances.get(destination).unwrap()
                         {
-                            next_distances.insert(destination.clone(), distance + 1);
+                            next_distances.insert(*destination, distance + 1);
                             changed = true;
                         }
                     }
@@ -285,8 +500,9 @@ impl Grid {
                 let position = Position::new(row as isize, col as isize);
                 let letter = match self.entities.get(&position) {
                     Some(Entity::Wall) => '#',
-                    Some(Entity::Letter(a)) => a.clone(),
+                    Some(Entity::Letter(a)) => *a,
                     Some(Entity::Nothing) => ' ',
+                    Some(Entity::Void) => '~',
                     None => ' ',
                 };
                 line.push(letter)
@@ -300,15 +516,72 @@ impl Grid {
 /// A level is a grid with chosen start and goal positions. If the level is "solved",
 /// then `words` will contain a series of strings that could connect the start and
 /// goal positions. The rules for this `connection` will be described later.
-#[derive(Serialize)]
+///
+/// `path` holds the junctions of the path from the start to the goal that the
+/// solution follows, and `seed` is the seed the level was generated from, so a level
+/// can be regenerated with the same configuration. The seed is written as a string,
+/// since it may not fit in a JavaScript number. Levels published before these
+/// were recorded read back with an empty path and a seed of 0.
+///
+/// `tiles` holds the player's letter tiles when they differ from the letters of
+/// `words`, because some of the solution's letters are already on the grid. It is
+/// left out of the JSON otherwise.
+#[derive(PartialEq, Eq, Clone, Debug, Serialize, Deserialize)]
 pub struct Level {
     pub start: Position,
     pub goal: Position,
     pub words: Vec<String>,
     pub grid: Grid,
+    #[serde(default)]
+    pub path: Vec<Position>,
+    #[serde(
+        default,
+        serialize_with = "serialize_seed",
+        deserialize_with = "deserialize_seed"
+    )]
+    pub seed: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tiles: Option<String>,
 }
 
 impl Level {
+    /// Returns how many of each letter the player has to build words with.
+    pub fn letters(&self) -> LetterBag {
+        match &self.tiles {
+            Some(tiles) => LetterBag::from_words([tiles.as_str()]),
+            None => LetterBag::from_words(self.words.iter().map(|word| word.as_str())),
+        }
+    }
+
+    /// Records the player's tiles as the letters of the words, less one for each
+    /// letter on the grid along the solution's `path`, which the player doesn't
+    /// need to place. Letters the path doesn't cross are still spelled by the words,
+    /// so keep their tiles. Nothing is recorded if the path crosses no letters.
+    /// Needs calling again if the words change.
+    pub fn record_tiles(&mut self) {
+        let covered: HashSet<Position> = self
+            .path
+            .windows(2)
+            .flat_map(|pair| segment_positions((pair[0], pair[1])))
+            .collect();
+        let locked: Vec<char> = self
+            .grid
+            .letters()
+            .into_iter()
+            .filter(|(position, _)| covered.contains(position))
+            .map(|(_, letter)| letter)
+            .collect();
+        if locked.is_empty() {
+            self.tiles = None;
+            return;
+        }
+        let mut tiles = LetterBag::from_words(self.words.iter().map(|word| word.as_str()));
+        for letter in locked {
+            tiles.take(letter);
+        }
+        self.tiles = Some(tiles.to_string());
+    }
+
     pub fn visualise(&self) {
         let bar = "=".repeat(self.grid.cols);
         println!("{}", bar);
@@ -323,8 +596,9 @@ impl Level {
                 } else {
                     let letter = match self.grid.entities.get(&position) {
                         Some(Entity::Wall) => '#',
-                        Some(Entity::Letter(a)) => a.clone(),
+                        Some(Entity::Letter(a)) => *a,
                         Some(Entity::Nothing) => ' ',
+                        Some(Entity::Void) => '~',
                         None => ' ',
                     };
                     line.push(letter)
@@ -334,5 +608,165 @@ impl Level {
         }
         println!("{}", bar);
         println!("Solution: {:?}", self.words);
+        if !self.path.is_empty() {
+            let path: Vec<String> = self.path.iter().map(|p| p.to_key()).collect();
+            println!("Path: {}", path.join(" -> "));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{level_rng, walls::WallStyle};
+    use clap::ValueEnum;
+
+    fn level() -> Level {
+        let mut grid = Grid::new(2, 3);
+        grid.entities.insert(Position::new(0, 2), Entity::Wall);
+        grid.entities
+            .insert(Position::new(1, 0), Entity::Letter('a'));
+        grid.entities.insert(Position::new(1, 2), Entity::Void);
+        Level {
+            start: Position::new(0, 0),
+            goal: Position::new(1, 1),
+            words: vec!["an".to_string(), "ta".to_string()],
+            grid,
+            path: vec![
+                Position::new(0, 0),
+                Position::new(1, 0),
+                Position::new(1, 1),
+            ],
+            seed: 42,
+            tiles: Some("nt".to_string()),
+        }
+    }
+
+    #[test]
+    fn level_round_trips_through_json() {
+        let level = level();
+        let json = serde_json::to_string(&level).unwrap();
+        let read: Level = serde_json::from_str(&json).unwrap();
+        assert_eq!(read, level);
+    }
+
+    #[test]
+    fn entities_serialize_with_their_type() {
+        let json = serde_json::to_value(level()).unwrap();
+        let entities = &json["grid"]["entities"];
+        assert_eq!(entities["0_2"], serde_json::json!({"type": "wall"}));
+        assert_eq!(
+            entities["1_0"],
+            serde_json::json!({"type": "letter", "value": "a"})
+        );
+        assert_eq!(entities["1_2"], serde_json::json!({"type": "void"}));
+    }
+
+    #[test]
+    fn same_grid_serializes_the_same() {
+        let first = serde_json::to_string(&level()).unwrap();
+        let second = serde_json::to_string(&level()).unwrap();
+        assert_eq!(first, second);
+    }
+
+    #[test]
+    fn old_levels_read_without_path_seed_or_tiles() {
+        let json = r#"{
+            "start": {"row": 0, "col": 0},
+            "goal": {"row": 0, "col": 2},
+            "words": ["cat", "xy"],
+            "grid": {"rows": 1, "cols": 3, "entities": {}}
+        }"#;
+        let level: Level = serde_json::from_str(json).unwrap();
+        assert!(level.path.is_empty());
+        assert_eq!(level.seed, 0);
+        assert_eq!(level.tiles, None);
+        assert_eq!(level.letters().to_string(), "actxy");
+    }
+
+    #[test]
+    fn seeds_are_written_as_strings() {
+        let mut level = level();
+        level.seed = u64::MAX;
+        let json = serde_json::to_value(&level).unwrap();
+        assert_eq!(json["seed"], serde_json::json!("18446744073709551615"));
+        assert_eq!(serde_json::from_value::<Level>(json).unwrap(), level);
+
+        // Seeds written as numbers still read back.
+        let mut json = serde_json::to_value(&level).unwrap();
+        json["seed"] = serde_json::json!(42);
+        assert_eq!(serde_json::from_value::<Level>(json).unwrap().seed, 42);
+    }
+
+    #[test]
+    fn invalid_position_keys_are_rejected() {
+        let json = r#"{
+            "start": {"row": 0, "col": 0},
+            "goal": {"row": 0, "col": 2},
+            "words": [],
+            "grid": {"rows": 1, "cols": 3, "entities": {"nope": {"type": "wall"}}}
+        }"#;
+        assert!(serde_json::from_str::<Level>(json).is_err());
+    }
+
+    #[test]
+    fn recorded_tiles_leave_out_letters_on_the_grid() {
+        let mut level = level();
+        level.tiles = None;
+        level.record_tiles();
+        assert_eq!(level.tiles.as_deref(), Some("ant"));
+        assert_eq!(level.letters().count('a'), 1);
+    }
+
+    #[test]
+    fn recorded_tiles_keep_letters_off_the_path() {
+        let mut level = level();
+        level.tiles = None;
+        level
+            .grid
+            .entities
+            .insert(Position::new(1, 0), Entity::Letter('t'));
+        level.path = vec![Position::new(0, 0), Position::new(0, 1)];
+        level.record_tiles();
+        assert_eq!(level.tiles, None);
+        assert_eq!(level.letters().count('t'), 1);
+    }
+
+    #[test]
+    fn initialised_walls_are_symmetric_and_connected() {
+        for &style in WallStyle::value_variants() {
+            let strategy = style.strategy();
+            for &symmetry in Symmetry::value_variants() {
+                for seed in 0..5 {
+                    // An even number of columns, so the vertical mirror has no
+                    // middle column.
+                    let mut grid = Grid::new(9, 10);
+                    grid.initialise_walls(strategy.as_ref(), symmetry, &mut level_rng(seed));
+                    for position in grid.all_positions() {
+                        if grid.is_wall(position) {
+                            for image in symmetry.images(position, grid.rows, grid.cols) {
+                                assert!(
+                                    grid.is_wall(image),
+                                    "{:?} {:?} {}: {:?} is a wall but {:?} is not",
+                                    style,
+                                    symmetry,
+                                    seed,
+                                    position,
+                                    image
+                                );
+                            }
+                        }
+                    }
+                    assert_eq!(
+                        grid.find_connected_sections().len(),
+                        1,
+                        "{:?} {:?} {}",
+                        style,
+                        symmetry,
+                        seed
+                    );
+                }
+            }
+        }
     }
 }
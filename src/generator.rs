@@ -1,25 +1,200 @@
+use clap::ValueEnum;
 use rand::prelude::*;
-use std::collections::HashSet;
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    fmt,
+};
 
 use crate::{
     DistanceMap, TurnsMap,
-    game::{Grid, Level},
-    position::Position,
-    solver::{Solution, WordList},
+    game::{Entity, Grid, Level, sorted_positions},
+    mask::Mask,
+    position::{Direction, Position},
+    room::{RoomRejection, RoomRequirements},
+    solver::{Solution, SolveOutcome, SolverConfig, WordList},
+    template::Template,
+    walls::{Symmetry, WallStrategy, WallStyle},
 };
 
+/// The word lists used by the generator. Solutions are built from the generation
+/// list of familiar words, while players may use any word from the permissive play
+/// list.
+pub struct Dictionaries {
+    generation: WordList,
+    play: WordList,
+}
+
+/// The words a player could build from a level's letters, split by whether the
+/// generator would have used them.
+pub struct AlternateWords<'a> {
+    /// Words in both the generation and play lists.
+    pub familiar: Vec<&'a str>,
+    /// Words only in the play list.
+    pub obscure: Vec<&'a str>,
+}
+
+impl Dictionaries {
+    /// Creates the dictionaries, removing any generation words that players
+    /// wouldn't be allowed to use, so every solution word is in both lists.
+    ///
+    /// Returns the dictionaries along with the generation words that were removed.
+    pub fn new(generation: WordList, play: WordList) -> (Self, Vec<String>) {
+        let removed: Vec<String> = generation
+            .words()
+            .filter(|word| !play.is_word_valid(word))
+            .map(|word| word.to_string())
+            .collect();
+        let generation = generation.intersection(&play);
+        (Self { generation, play }, removed)
+    }
+
+    /// The list solutions are built from.
+    pub fn generation(&self) -> &WordList {
+        &self.generation
+    }
+
+    /// The list of every word players may use.
+    pub fn play(&self) -> &WordList {
+        &self.play
+    }
+
+    /// Returns the words other than the level's own that players could legally
+    /// build from its letters. Levels with many obscure alternates may be
+    /// trivialised by words the generator would never have chosen.
+    pub fn alternate_words(&self, level: &Level) -> AlternateWords<'_> {
+        let (familiar, obscure) = self
+            .play
+            .words_from_bag(&level.letters(), &[])
+            .into_iter()
+            .filter(|word| !level.words.iter().any(|own| own == word))
+            .partition(|word| self.generation.is_word_valid(word));
+        AlternateWords { familiar, obscure }
+    }
+}
+
+/// How the solution words of recent levels are treated when generating the next.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum HistoryMode {
+    /// Recent solution words are never used.
+    Exclude,
+    /// Recent solution words are less likely to be chosen.
+    DownWeight,
+}
+
+impl HistoryMode {
+    /// Returns the word list to generate the next level from, avoiding the recent
+    /// solution words. `weight` is how likely a recent word is to be chosen when
+    /// down weighting, relative to other words.
+    pub fn apply(self, word_list: &WordList, recent: &HashSet<String>, weight: f64) -> WordList {
+        match self {
+            HistoryMode::Exclude => word_list.without(recent),
+            HistoryMode::DownWeight => word_list.down_weighted(recent, weight),
+        }
+    }
+}
+
+/// Options for the paths between the start and goal that a solution may follow.
+#[derive(Debug, Clone)]
+pub struct PathOptions {
+    /// How many more turns than the minimum a path may take.
+    pub extra_turns: usize,
+    /// How many more squares than the shortest path a path may cover.
+    pub extra_length: usize,
+    /// The most paths to try solving before the level is discarded.
+    pub max_paths: usize,
+}
+
+/// The ways generating a level can fail.
+#[derive(Debug, PartialEq, Eq)]
+pub enum GenerateError {
+    /// The template's fixed start and goal can't be joined by any solution.
+    Unsolvable,
+    /// None of the candidates were accepted.
+    TooManyCandidates(u64),
+}
+
+impl fmt::Display for GenerateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GenerateError::Unsolvable => {
+                write!(f, "no solution joins the template's start and goal")
+            }
+            GenerateError::TooManyCandidates(count) => {
+                write!(f, "none of {} candidate levels were accepted", count)
+            }
+        }
+    }
+}
+
+impl std::error::Error for GenerateError {}
+
+/// Options controlling how levels are generated.
+#[derive(Debug, Clone)]
+pub struct GeneratorConfig {
+    pub rows: usize,
+    pub cols: usize,
+    pub solver: SolverConfig,
+    pub paths: PathOptions,
+    pub walls: WallStyle,
+    pub symmetry: Symmetry,
+    /// Guarantees on the room each level is placed in.
+    pub room: RoomRequirements,
+    /// The most candidate levels to try before giving up on a level.
+    pub max_candidates: u64,
+    /// How many of each solution's letters are placed on the grid as locked tiles.
+    pub locked_letters: usize,
+    /// The outline of the board, in which case `rows` and `cols` are ignored.
+    pub mask: Option<Mask>,
+    /// A hand drawn board to use instead of generating walls, in which case
+    /// `rows`, `cols`, `mask`, `walls`, `symmetry` and `room` are ignored.
+    pub template: Option<Template>,
+}
+
 /// A model used to generate levels from information extracted from the grid.
 pub struct LevelGenerator {
     pub grid: Grid,
     pub free_space: HashSet<Position>,
     pub turns_map: TurnsMap,
     pub distance_map: DistanceMap,
+    /// The start and goal positions every level must use, if fixed.
+    pub start: Option<Position>,
+    pub goal: Option<Position>,
 }
 
 impl LevelGenerator {
-    /// Creates a level generator from a grid.
-    pub fn from_grid(mut grid: Grid) -> Self {
-        let free_space = grid.initialise_walls();
+    /// Creates a level generator from a grid, placing walls with the strategy and
+    /// symmetry. Returns why the grid was rejected if its room can't be made to
+    /// meet the requirements.
+    pub fn from_grid<R: Rng + ?Sized>(
+        mut grid: Grid,
+        walls: &dyn WallStrategy,
+        symmetry: Symmetry,
+        requirements: &RoomRequirements,
+        rng: &mut R,
+    ) -> Result<Self, RoomRejection> {
+        grid.initialise_walls(walls, symmetry, rng);
+        let free_space = requirements.enforce(&mut grid, symmetry)?;
+        let turns_map = grid.generate_turns_map();
+        let distance_map = grid.generate_distance_map();
+
+        Ok(Self {
+            grid,
+            free_space,
+            turns_map,
+            distance_map,
+            start: None,
+            goal: None,
+        })
+    }
+
+    /// Creates a level generator from a hand drawn board, keeping its walls and
+    /// letters as they are along with any start and goal it marks.
+    ///
+    /// Levels are placed in the room holding the start or goal, or the largest
+    /// room if neither is marked.
+    pub fn from_template(template: &Template) -> Self {
+        let grid = template.grid.clone();
+        let free_space = template.room();
         let turns_map = grid.generate_turns_map();
         let distance_map = grid.generate_distance_map();
 
@@ -28,6 +203,8 @@ impl LevelGenerator {
             free_space,
             turns_map,
             distance_map,
+            start: template.start,
+            goal: template.goal,
         }
     }
 
@@ -35,60 +212,299 @@ impl LevelGenerator {
     ///
     /// Does this in two main steps:
     /// - Choose start and goal posiitons
-    /// - Attempt to solve the level, up to `solver_retries` tries.
-    pub fn attempt_generate_level(
+    /// - Attempt to solve the level with the configured solver, along each of the
+    ///   paths from `find_paths` in turn.
+    ///
+    /// On failure, returns `SolveOutcome::Unsolvable` if every path was ruled out,
+    /// or `SolveOutcome::GaveUp` otherwise.
+    pub fn attempt_generate_level<R: Rng + ?Sized>(
         &self,
         word_list: &WordList,
-        solver_retries: usize,
-    ) -> Option<Level> {
-        let (start, goal) = self.choose_start_and_goal()?;
+        config: &GeneratorConfig,
+        rng: &mut R,
+    ) -> Result<Level, SolveOutcome> {
+        let Some((start, goal)) = self.choose_start_and_goal(rng) else {
+            return Err(SolveOutcome::GaveUp);
+        };
 
         let mut level = Level {
             start,
             goal,
             grid: self.grid.clone(),
             words: Vec::new(),
+            path: Vec::new(),
+            seed: 0,
+            tiles: None,
         };
 
-        let junctions = self.find_path_junctions(start, goal);
-        let segments = LevelGenerator::extract_segments(junctions);
+        let mut outcome = SolveOutcome::Unsolvable;
+        for junctions in self.find_paths(start, goal, &config.paths) {
+            let segments = LevelGenerator::extract_segments(junctions.clone());
+            let mut solution = Solution::new(segments).with_letters(self.grid.letters());
+            match solution.solve(word_list, &config.solver, rng) {
+                SolveOutcome::Solved => {
+                    level.words = solution.all_words().into_iter().cloned().collect();
+                    level.path = junctions;
+                    if config.locked_letters > 0 {
+                        lock_letters(&mut level, &solution, config.locked_letters, rng);
+                    }
+                    level.record_tiles();
+                    return Ok(level);
+                }
+                SolveOutcome::Unsolvable => {}
+                SolveOutcome::GaveUp => outcome = SolveOutcome::GaveUp,
+            }
+        }
+        Err(outcome)
+    }
+
+    /// Returns the junctions of up to `options.max_paths` paths from the start to
+    /// the goal, which never cross themselves.
+    ///
+    /// Every minimum-turn path comes first, followed by paths with each extra turn
+    /// allowed. Paths with the same number of turns are ordered by length. If none
+    /// are found, the path from the turns map is returned instead.
+    pub fn find_paths(
+        &self,
+        start: Position,
+        goal: Position,
+        options: &PathOptions,
+    ) -> Vec<Vec<Position>> {
+        let segments_left = self.segments_left_map(goal);
+        let (Some(min_segments), Some(distance)) = (
+            self.min_segments(&segments_left, start, None, goal),
+            self.distance_map.get(start, goal),
+        ) else {
+            return vec![self.find_path_junctions(start, goal)];
+        };
 
-        let mut solution = Solution::new(segments);
-        if let None = solution.attempt_solve(word_list, solver_retries) {
-            return None;
+        let mut result = Vec::new();
+        for segments in min_segments..=min_segments + options.extra_turns {
+            if result.len() >= options.max_paths {
+                break;
+            }
+            let mut paths = Vec::new();
+            let mut visited = HashSet::from([start]);
+            let mut junctions = vec![start];
+            self.extend_path(
+                goal,
+                &segments_left,
+                segments,
+                distance + options.extra_length,
+                None,
+                &mut visited,
+                &mut junctions,
+                &mut paths,
+            );
+            paths.sort_by_key(|path| path_length(path));
+            result.extend(paths);
         }
+        if result.is_empty() {
+            result.push(self.find_path_junctions(start, goal));
+        }
+        result.truncate(options.max_paths.max(1));
+        result
+    }
 
-        level.words = solution.all_words().into_iter().cloned().collect();
-        Some(level)
+    /// Maps each square and direction to the fewest segments still needed to
+    /// reach the goal after arriving at the square moving in that direction, not
+    /// counting the segment being moved along.
+    ///
+    /// Unlike the turns map, which keeps a single direction for each square, this
+    /// gives the exact minimum for every direction of arrival. It is found with a
+    /// 0-1 breadth first search out from the goal, where turning costs a segment.
+    fn segments_left_map(&self, goal: Position) -> HashMap<(Position, Direction), usize> {
+        let directions = [
+            Direction::Up,
+            Direction::Down,
+            Direction::Left,
+            Direction::Right,
+        ];
+        let mut result = HashMap::new();
+        let mut queue = VecDeque::new();
+        for direction in directions {
+            result.insert((goal, direction), 0);
+            queue.push_back((goal, direction));
+        }
+
+        while let Some((position, direction)) = queue.pop_front() {
+            let segments = result[&(position, direction)];
+            // Squares that step onto this one moving in the direction.
+            let previous = position.step_in_direction(direction.opposite());
+            if !self.free_space.contains(&previous) {
+                continue;
+            }
+            for previous_direction in directions {
+                let turns = usize::from(previous_direction != direction);
+                let key = (previous, previous_direction);
+                if result
+                    .get(&key)
+                    .is_some_and(|&known| known <= segments + turns)
+                {
+                    continue;
+                }
+                result.insert(key, segments + turns);
+                match turns {
+                    0 => queue.push_front(key),
+                    _ => queue.push_back(key),
+                }
+            }
+        }
+        result
+    }
+
+    /// Returns the fewest segments joining the position to the goal, where the
+    /// first can't carry on in `last_direction`, or `None` if the goal can't be
+    /// reached.
+    fn min_segments(
+        &self,
+        segments_left: &HashMap<(Position, Direction), usize>,
+        position: Position,
+        last_direction: Option<Direction>,
+        goal: Position,
+    ) -> Option<usize> {
+        if position == goal {
+            return Some(0);
+        }
+        [
+            Direction::Up,
+            Direction::Down,
+            Direction::Left,
+            Direction::Right,
+        ]
+        .into_iter()
+        .filter(|&direction| Some(direction) != last_direction)
+        .filter_map(|direction| {
+            let next = position.step_in_direction(direction);
+            segments_left.get(&(next, direction)).map(|left| left + 1)
+        })
+        .min()
+    }
+
+    /// Depth first search for the paths to the goal made of exactly
+    /// `segments_left` more straight segments, covering at most `length_left` more
+    /// squares.
+    #[allow(clippy::too_many_arguments)]
+    fn extend_path(
+        &self,
+        goal: Position,
+        segments_left_map: &HashMap<(Position, Direction), usize>,
+        segments_left: usize,
+        length_left: usize,
+        last_direction: Option<Direction>,
+        visited: &mut HashSet<Position>,
+        junctions: &mut Vec<Position>,
+        paths: &mut Vec<Vec<Position>>,
+    ) {
+        let position = *junctions.last().unwrap();
+        if position == goal {
+            if segments_left == 0 {
+                paths.push(junctions.clone());
+            }
+            return;
+        }
+
+        // Prune paths that can no longer reach the goal in time.
+        match (
+            self.min_segments(segments_left_map, position, last_direction, goal),
+            self.distance_map.get(position, goal),
+        ) {
+            (Some(segments), Some(distance))
+                if segments <= segments_left && *distance <= length_left => {}
+            _ => return,
+        }
+
+        for direction in [
+            Direction::Up,
+            Direction::Down,
+            Direction::Left,
+            Direction::Right,
+        ] {
+            // Carrying on in the same direction isn't a new segment.
+            if Some(direction) == last_direction {
+                continue;
+            }
+
+            let mut covered = Vec::new();
+            let mut next = position.step_in_direction(direction);
+            while covered.len() < length_left
+                && self.free_space.contains(&next)
+                && !visited.contains(&next)
+            {
+                covered.push(next);
+                visited.insert(next);
+                junctions.push(next);
+                self.extend_path(
+                    goal,
+                    segments_left_map,
+                    segments_left - 1,
+                    length_left - covered.len(),
+                    Some(direction),
+                    visited,
+                    junctions,
+                    paths,
+                );
+                junctions.pop();
+                if next == goal {
+                    break;
+                }
+                next = next.step_in_direction(direction);
+            }
+            for position in covered {
+                visited.remove(&position);
+            }
+        }
     }
 
-    pub fn choose_start_and_goal(&self) -> Option<(Position, Position)> {
-        let start = self.free_space.iter().choose(&mut rand::rng())?;
-        let start_deltas = self.distance_map.0.get(start)?;
-        let start_turns = self.turns_map.0.get(start)?;
+    /// Chooses the start and goal of a level, using the fixed ones if there are
+    /// any. A position that isn't fixed is chosen to be far from the other.
+    pub fn choose_start_and_goal<R: Rng + ?Sized>(
+        &self,
+        rng: &mut R,
+    ) -> Option<(Position, Position)> {
+        match (self.start, self.goal) {
+            (Some(start), Some(goal)) => Some((start, goal)),
+            (Some(start), None) => Some((start, self.choose_far_from(start, rng)?)),
+            (None, Some(goal)) => Some((self.choose_far_from(goal, rng)?, goal)),
+            (None, None) => {
+                let free_space = sorted_positions(&self.free_space);
+                let start = *free_space.iter().choose(rng)?;
+                Some((start, self.choose_far_from(start, rng)?))
+            }
+        }
+    }
 
-        let mut candidates = self.free_space.clone();
-        candidates.remove(start);
+    /// Chooses a random square from the third of the free space furthest from the
+    /// position, taking into account both distance and turns.
+    fn choose_far_from<R: Rng + ?Sized>(
+        &self,
+        position: Position,
+        rng: &mut R,
+    ) -> Option<Position> {
+        let free_space = sorted_positions(&self.free_space);
+        let deltas = self.distance_map.0.get(&position)?;
+        let turns = self.turns_map.0.get(&position)?;
 
-        let mut candidates: Vec<_> = candidates.iter().collect();
+        let mut candidates: Vec<_> = free_space.iter().filter(|&p| *p != position).collect();
         // Take into account distance and turns
-        candidates.sort_by_key(|&p| {
-            start_deltas.get(p).unwrap_or(&0) + start_turns.get(p).map(|v| v.0).unwrap_or(0)
-        });
+        candidates
+            .sort_by_key(|&p| deltas.get(p).unwrap_or(&0) + turns.get(p).map(|v| v.0).unwrap_or(0));
 
         // Choose from latter third
         let count = candidates.len();
-        let candidates: Vec<_> = candidates.into_iter().skip(count * 2 / 3).collect();
-
-        let start = *start;
-        let goal = candidates.into_iter().choose(&mut rand::rng())?.clone();
-        Some((start, goal))
+        candidates
+            .into_iter()
+            .skip(count * 2 / 3)
+            .choose(rng)
+            .copied()
     }
 
+    /// Follows the turns map from the start to the goal, returning the junctions of
+    /// a minimum-turn path.
     fn find_path_junctions(&self, start: Position, goal: Position) -> Vec<Position> {
         let mut position = start;
         let mut path = vec![start];
-        let mut turns_left = self.turns_map.get(position, goal).unwrap().0.clone();
+        let mut turns_left = self.turns_map.get(position, goal).unwrap().0;
 
         while position != goal {
             let (turns, direction) = self.turns_map.get(position, goal).unwrap();
@@ -119,3 +535,173 @@ impl LevelGenerator {
             .collect()
     }
 }
+
+/// Places up to `count` of the solution's letters on the level's grid, so players
+/// start with them as locked tiles. They are chosen at random from the squares the
+/// solution covers, other than the start, the goal and letters already on the grid.
+fn lock_letters<R: Rng + ?Sized>(
+    level: &mut Level,
+    solution: &Solution,
+    count: usize,
+    rng: &mut R,
+) {
+    let letters = solution.placed_letters();
+    let mut squares: Vec<Position> = letters
+        .keys()
+        .copied()
+        .filter(|p| *p != level.start && *p != level.goal)
+        .filter(|p| !matches!(level.grid.entities.get(p), Some(Entity::Letter(_))))
+        .collect();
+    squares.sort();
+    for position in squares.choose_multiple(rng, count) {
+        level
+            .grid
+            .entities
+            .insert(*position, Entity::Letter(letters[position]));
+    }
+}
+
+/// Returns the number of steps taken along a path of junctions.
+fn path_length(junctions: &[Position]) -> usize {
+    junctions
+        .windows(2)
+        .map(|pair| pair[0].manhattan_distance(pair[1]))
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{solver::tests::word_list, template::parse_template};
+
+    #[test]
+    fn generation_words_must_be_playable() {
+        let blocked = HashSet::from(["dam".to_string()]);
+        let generation = word_list(&["cat", "act", "dam", "zyx"]).without(&blocked);
+        let play = word_list(&["cat", "act", "tac", "dam", "mad"]).without(&blocked);
+        let (dictionaries, removed) = Dictionaries::new(generation, play);
+
+        assert_eq!(removed, vec!["zyx"]);
+        assert_eq!(
+            dictionaries.generation().words().collect::<Vec<_>>(),
+            vec!["act", "cat"]
+        );
+        assert!(!dictionaries.play().is_word_valid("dam"));
+        assert!(dictionaries.play().is_word_valid("mad"));
+    }
+
+    #[test]
+    fn recent_words_are_excluded_or_down_weighted() {
+        let words = word_list(&["cat", "dog", "hen"]);
+        let recent = HashSet::from(["cat".to_string(), "owl".to_string()]);
+
+        let excluded = HistoryMode::Exclude.apply(&words, &recent, 0.1);
+        assert_eq!(excluded.words().collect::<Vec<_>>(), vec!["dog", "hen"]);
+
+        let down_weighted = HistoryMode::DownWeight.apply(&words, &recent, 0.1);
+        assert_eq!(down_weighted.size(), 3);
+        assert_eq!(down_weighted.weight("cat"), 0.1);
+        assert_eq!(down_weighted.weight("dog"), 1.0);
+        // Weights compound with those already given.
+        let twice = HistoryMode::DownWeight.apply(&down_weighted, &recent, 0.5);
+        assert_eq!(twice.weight("cat"), 0.05);
+    }
+
+    #[test]
+    fn alternate_words_are_split_by_familiarity() {
+        let (dictionaries, _) = Dictionaries::new(
+            word_list(&["cat", "act", "at"]),
+            word_list(&["cat", "act", "tac", "at", "cast"]),
+        );
+        let level = Level {
+            start: Position::new(0, 0),
+            goal: Position::new(0, 2),
+            grid: Grid::new(1, 3),
+            words: vec!["cat".to_string()],
+            path: Vec::new(),
+            seed: 0,
+            tiles: None,
+        };
+        let alternates = dictionaries.alternate_words(&level);
+        // Every word the letters spell, other than the solution's own.
+        assert_eq!(alternates.familiar, vec!["at", "act"]);
+        // Only in the play list, and "cast" needs a tile the level doesn't have.
+        assert_eq!(alternates.obscure, vec!["tac"]);
+    }
+
+    fn options(extra_turns: usize, extra_length: usize) -> PathOptions {
+        PathOptions {
+            extra_turns,
+            extra_length,
+            max_paths: 100,
+        }
+    }
+
+    fn segment_count(path: &[Position]) -> usize {
+        path.len() - 1
+    }
+
+    #[test]
+    fn finds_every_minimum_turn_path_first() {
+        let generator = LevelGenerator::from_template(&parse_template("S...\n....\n...G").unwrap());
+        let (start, goal) = (Position::new(0, 0), Position::new(2, 3));
+        let paths = generator.find_paths(start, goal, &options(1, 0));
+
+        assert_eq!(
+            paths[..2],
+            [
+                vec![start, Position::new(2, 0), goal],
+                vec![start, Position::new(0, 3), goal],
+            ]
+        );
+        assert!(paths[2..].iter().all(|path| segment_count(path) == 3));
+        for path in &paths {
+            assert_eq!(path_length(path), start.manhattan_distance(goal));
+            // Every segment is straight, and turns from the last.
+            for pair in path.windows(2) {
+                assert!(pair[0].row == pair[1].row || pair[0].col == pair[1].col);
+            }
+            for triple in path.windows(3) {
+                assert!((triple[0].row == triple[1].row) != (triple[1].row == triple[2].row),);
+            }
+        }
+    }
+
+    #[test]
+    fn finds_minimum_turn_paths_the_turns_map_misses() {
+        // The turns map only keeps one direction for each square, so it thinks
+        // the start needs four segments to reach the goal, but the path around
+        // the top only needs three.
+        let template = parse_template(".....\n...#.\n...#.\n...#.\nS#..G").unwrap();
+        let generator = LevelGenerator::from_template(&template);
+        let (start, goal) = (Position::new(4, 0), Position::new(4, 4));
+        assert_eq!(generator.turns_map.get(start, goal).unwrap().0, 4);
+
+        let paths = generator.find_paths(start, goal, &options(0, 100));
+        assert_eq!(
+            paths,
+            vec![vec![start, Position::new(0, 0), Position::new(0, 4), goal]]
+        );
+    }
+
+    #[test]
+    fn paths_respect_the_limits() {
+        let generator = LevelGenerator::from_template(&parse_template("S...\n.#..\n...G").unwrap());
+        let (start, goal) = (Position::new(0, 0), Position::new(2, 3));
+        let paths = generator.find_paths(start, goal, &options(2, 2));
+        for path in &paths {
+            assert!(segment_count(path) <= 4);
+            assert!(path_length(path) <= 7);
+        }
+        assert!(paths.windows(2).all(|pair| {
+            (segment_count(&pair[0]), path_length(&pair[0]))
+                <= (segment_count(&pair[1]), path_length(&pair[1]))
+        }));
+
+        let limited = PathOptions {
+            max_paths: 2,
+            ..options(2, 2)
+        };
+        assert_eq!(generator.find_paths(start, goal, &limited), paths[..2]);
+    }
+}
@@ -0,0 +1,217 @@
+use clap::ValueEnum;
+use std::collections::HashMap;
+
+/// The language of an edition of the game, which decides the letters words may
+/// contain.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Language {
+    English,
+    Spanish,
+    German,
+}
+
+/// Where the letters used to pad a level's letters are sampled from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum LetterFrequencies {
+    /// How often each letter appears in the generation word list.
+    WordList,
+    /// How often each letter appears in running text of the language.
+    Language,
+}
+
+const ENGLISH_ALPHABET: &str = "abcdefghijklmnopqrstuvwxyz";
+const SPANISH_ALPHABET: &str = "abcdefghijklmnñopqrstuvwxyz";
+const GERMAN_ALPHABET: &str = "abcdefghijklmnopqrstuvwxyzäöüß";
+
+/// Letter frequencies in running text, in hundredths of a percent.
+const ENGLISH_FREQUENCIES: [(char, usize); 26] = [
+    ('a', 817),
+    ('b', 149),
+    ('c', 278),
+    ('d', 425),
+    ('e', 1270),
+    ('f', 223),
+    ('g', 202),
+    ('h', 609),
+    ('i', 697),
+    ('j', 15),
+    ('k', 77),
+    ('l', 403),
+    ('m', 241),
+    ('n', 675),
+    ('o', 751),
+    ('p', 193),
+    ('q', 10),
+    ('r', 599),
+    ('s', 633),
+    ('t', 906),
+    ('u', 276),
+    ('v', 98),
+    ('w', 236),
+    ('x', 15),
+    ('y', 197),
+    ('z', 7),
+];
+
+const SPANISH_FREQUENCIES: [(char, usize); 27] = [
+    ('a', 1253),
+    ('b', 142),
+    ('c', 468),
+    ('d', 586),
+    ('e', 1368),
+    ('f', 69),
+    ('g', 101),
+    ('h', 70),
+    ('i', 625),
+    ('j', 44),
+    ('k', 2),
+    ('l', 497),
+    ('m', 315),
+    ('n', 671),
+    ('ñ', 31),
+    ('o', 868),
+    ('p', 251),
+    ('q', 88),
+    ('r', 687),
+    ('s', 798),
+    ('t', 463),
+    ('u', 393),
+    ('v', 90),
+    ('w', 1),
+    ('x', 22),
+    ('y', 90),
+    ('z', 52),
+];
+
+const GERMAN_FREQUENCIES: [(char, usize); 30] = [
+    ('a', 651),
+    ('b', 189),
+    ('c', 306),
+    ('d', 508),
+    ('e', 1740),
+    ('f', 166),
+    ('g', 301),
+    ('h', 476),
+    ('i', 755),
+    ('j', 27),
+    ('k', 121),
+    ('l', 344),
+    ('m', 253),
+    ('n', 978),
+    ('o', 251),
+    ('p', 79),
+    ('q', 2),
+    ('r', 700),
+    ('s', 727),
+    ('t', 615),
+    ('u', 435),
+    ('v', 67),
+    ('w', 189),
+    ('x', 3),
+    ('y', 4),
+    ('z', 113),
+    ('ä', 54),
+    ('ö', 30),
+    ('ü', 65),
+    ('ß', 31),
+];
+
+impl Language {
+    /// The letters words in the language may contain, after normalisation.
+    pub fn alphabet(self) -> &'static str {
+        match self {
+            Language::English => ENGLISH_ALPHABET,
+            Language::Spanish => SPANISH_ALPHABET,
+            Language::German => GERMAN_ALPHABET,
+        }
+    }
+
+    /// Returns true if the letter is in the language's alphabet.
+    pub fn contains(self, letter: char) -> bool {
+        self.alphabet().contains(letter)
+    }
+
+    /// Trims and lowercases the word, folding accents the game doesn't distinguish
+    /// between onto their base letter. Spanish tiles don't carry accents, so `á`
+    /// becomes `a`, while `ñ` is a letter of its own.
+    ///
+    /// Returns `None` if the word has letters outside the alphabet.
+    pub fn normalise(self, word: &str) -> Option<String> {
+        let word: String = word
+            .trim()
+            .to_lowercase()
+            .chars()
+            .map(|letter| self.fold(letter))
+            .collect();
+        word.chars()
+            .all(|letter| self.contains(letter))
+            .then_some(word)
+    }
+
+    /// How often each letter appears in running text of the language, in
+    /// hundredths of a percent.
+    pub fn letter_frequencies(self) -> HashMap<char, usize> {
+        let frequencies: &[(char, usize)] = match self {
+            Language::English => &ENGLISH_FREQUENCIES,
+            Language::Spanish => &SPANISH_FREQUENCIES,
+            Language::German => &GERMAN_FREQUENCIES,
+        };
+        frequencies.iter().copied().collect()
+    }
+
+    fn fold(self, letter: char) -> char {
+        match (self, letter) {
+            (Language::Spanish, 'á') => 'a',
+            (Language::Spanish, 'é') => 'e',
+            (Language::Spanish, 'í') => 'i',
+            (Language::Spanish, 'ó') => 'o',
+            (Language::Spanish, 'ú' | 'ü') => 'u',
+            _ => letter,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn spanish_folds_accents_but_keeps_the_tilde_n() {
+        assert_eq!(
+            Language::Spanish.normalise(" Año "),
+            Some("año".to_string())
+        );
+        assert_eq!(
+            Language::Spanish.normalise("Árbol"),
+            Some("arbol".to_string())
+        );
+        assert_eq!(
+            Language::Spanish.normalise("pingüino"),
+            Some("pinguino".to_string())
+        );
+        assert_eq!(Language::Spanish.fold('ñ'), 'ñ');
+        assert_eq!(Language::Spanish.normalise("straße"), None);
+    }
+
+    #[test]
+    fn german_keeps_umlauts_and_eszett() {
+        assert_eq!(
+            Language::German.normalise("Straße"),
+            Some("straße".to_string())
+        );
+        assert_eq!(Language::German.normalise("Über"), Some("über".to_string()));
+        assert_eq!(Language::German.fold('ü'), 'ü');
+        assert_eq!(Language::German.normalise("año"), None);
+    }
+
+    #[test]
+    fn english_rejects_accented_letters() {
+        assert_eq!(
+            Language::English.normalise("Cafe"),
+            Some("cafe".to_string())
+        );
+        assert_eq!(Language::English.normalise("café"), None);
+        assert_eq!(Language::English.normalise("naïve"), None);
+        assert_eq!(Language::English.normalise("x-ray"), None);
+    }
+}
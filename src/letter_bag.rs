@@ -0,0 +1,121 @@
+use std::{collections::HashMap, fmt};
+
+/// A multiset of letter tiles, like the letters a player is given for a level.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LetterBag {
+    counts: HashMap<char, usize>,
+}
+
+impl LetterBag {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns a bag holding every letter of the words.
+    pub fn from_words<'a>(words: impl IntoIterator<Item = &'a str>) -> Self {
+        let mut bag = Self::new();
+        for word in words {
+            bag.extend(word.chars());
+        }
+        bag
+    }
+
+    /// Adds a tile of the letter to the bag.
+    pub fn add(&mut self, letter: char) {
+        *self.counts.entry(letter).or_insert(0) += 1;
+    }
+
+    /// Adds a tile for each of the letters to the bag.
+    pub fn extend(&mut self, letters: impl IntoIterator<Item = char>) {
+        for letter in letters {
+            self.add(letter);
+        }
+    }
+
+    /// Takes a tile of the letter out of the bag, returning false if there are
+    /// none left.
+    pub fn take(&mut self, letter: char) -> bool {
+        match self.counts.get_mut(&letter) {
+            Some(count) if *count > 0 => {
+                *count -= 1;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Returns how many tiles of the letter are in the bag.
+    pub fn count(&self, letter: char) -> usize {
+        self.counts.get(&letter).copied().unwrap_or(0)
+    }
+
+    /// Returns the total number of tiles in the bag.
+    pub fn len(&self) -> usize {
+        self.counts.values().sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns true if the word can be spelled from the bag's tiles. The letters
+    /// at the `fixed` indexes are already on the board, so don't use a tile.
+    pub fn can_build(&self, word: &str, fixed: &[usize]) -> bool {
+        let mut remaining = self.clone();
+        word.chars()
+            .enumerate()
+            .all(|(index, letter)| fixed.contains(&index) || remaining.take(letter))
+    }
+}
+
+impl fmt::Display for LetterBag {
+    /// Writes the tiles in alphabetical order, e.g. `aabst`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut letters: Vec<(&char, &usize)> = self.counts.iter().collect();
+        letters.sort();
+        for (letter, count) in letters {
+            for _ in 0..*count {
+                write!(f, "{}", letter)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn repeated_letters_need_a_tile_each() {
+        let bag = LetterBag::from_words(["tap"]);
+        assert!(bag.can_build("pat", &[]));
+        assert!(!bag.can_build("tapa", &[]));
+        assert!(LetterBag::from_words(["tap", "a"]).can_build("tapa", &[]));
+    }
+
+    #[test]
+    fn building_a_word_exhausts_the_bag() {
+        let mut bag = LetterBag::from_words(["aab"]);
+        assert_eq!(bag.len(), 3);
+        assert!(bag.can_build("baa", &[]));
+        // Checking a word doesn't use up the tiles.
+        assert_eq!(bag.to_string(), "aab");
+
+        assert!(bag.take('a') && bag.take('a') && bag.take('b'));
+        assert!(!bag.take('a'));
+        assert!(bag.is_empty());
+        assert!(!bag.can_build("a", &[]));
+        assert!(bag.can_build("", &[]));
+    }
+
+    #[test]
+    fn fixed_letters_do_not_use_a_tile() {
+        let bag = LetterBag::from_words(["at"]);
+        assert!(!bag.can_build("cat", &[]));
+        assert!(bag.can_build("cat", &[0]));
+        // Only the letter at the fixed index is free.
+        assert!(!bag.can_build("tat", &[1]));
+        assert!(bag.can_build("tat", &[0, 2]));
+    }
+}
@@ -0,0 +1,373 @@
+use rand::distr::weighted::WeightedIndex;
+use rand::prelude::*;
+use rand_chacha::ChaCha8Rng;
+
+use std::{
+    collections::HashMap,
+    sync::{
+        Mutex,
+        atomic::{AtomicBool, AtomicU64, Ordering},
+    },
+    thread,
+};
+
+use chrono::{DateTime, Days, Utc};
+use edge_map::EdgeMap;
+use game::{Grid, Level};
+use generator::{GenerateError, GeneratorConfig, LevelGenerator};
+use position::Direction;
+use room::RoomRejection;
+use solver::{SolveOutcome, WordList};
+
+pub mod archive;
+pub mod csp;
+pub mod edge_map;
+pub mod game;
+pub mod generator;
+pub mod language;
+pub mod letter_bag;
+pub mod mask;
+pub mod pattern;
+pub mod position;
+pub mod room;
+pub mod solver;
+pub mod template;
+pub mod validator;
+pub mod walls;
+pub mod words;
+
+pub type DistanceMap = EdgeMap<usize>;
+pub type TurnsMap = EdgeMap<(usize, Option<Direction>)>;
+
+/// Generates a single level that satisfies the predicate function, spreading the
+/// candidate levels across `threads` worker threads.
+///
+/// Candidate `i` draws all of its randomness from an rng seeded with
+/// `candidate_seed(seed, i)`, and the first accepted candidate by index is
+/// returned. This means the same seed and arguments always produce the same level,
+/// regardless of the number of threads.
+///
+/// Also returns why each candidate grid before the accepted one was rejected for
+/// not meeting the configured room requirements.
+///
+/// Gives up after `config.max_candidates` candidates. A template with a fixed
+/// start and goal gives every candidate the same paths, so generation stops as
+/// soon as one candidate proves none of them can be solved.
+pub fn generate_level(
+    word_list: &WordList,
+    config: &GeneratorConfig,
+    seed: u64,
+    threads: usize,
+    pred: impl Fn(&Level) -> bool + Sync,
+) -> Result<(Level, Vec<RoomRejection>), GenerateError> {
+    let walls = config.walls.strategy();
+    // A template's board is the same for every candidate, so only needs
+    // analysing once.
+    let template = config.template.as_ref().map(LevelGenerator::from_template);
+    let fixed_paths = template
+        .as_ref()
+        .is_some_and(|generator| generator.start.is_some() && generator.goal.is_some());
+    let unsolvable = AtomicBool::new(false);
+    let next_candidate = AtomicU64::new(0);
+    let accepted: Mutex<Option<(u64, Level)>> = Mutex::new(None);
+    let rejected: Mutex<Vec<(u64, RoomRejection)>> = Mutex::new(Vec::new());
+
+    thread::scope(|scope| {
+        for _ in 0..threads.max(1) {
+            scope.spawn(|| {
+                loop {
+                    let candidate = next_candidate.fetch_add(1, Ordering::Relaxed);
+                    if candidate >= config.max_candidates || unsolvable.load(Ordering::Relaxed) {
+                        break;
+                    }
+                    // Every candidate before an accepted one has already been claimed,
+                    // so there is nothing left to do once we've passed it.
+                    if let Some((index, _)) = accepted.lock().unwrap().as_ref()
+                        && *index < candidate
+                    {
+                        break;
+                    }
+
+                    let mut rng = level_rng(candidate_seed(seed, candidate));
+                    let generated;
+                    let generator = match &template {
+                        Some(generator) => generator,
+                        None => {
+                            let grid = match &config.mask {
+                                Some(mask) => Grid::from_mask(mask),
+                                None => Grid::new(config.rows, config.cols),
+                            };
+                            match LevelGenerator::from_grid(
+                                grid,
+                                walls.as_ref(),
+                                config.symmetry,
+                                &config.room,
+                                &mut rng,
+                            ) {
+                                Ok(generator) => generated = generator,
+                                Err(rejection) => {
+                                    rejected.lock().unwrap().push((candidate, rejection));
+                                    continue;
+                                }
+                            }
+                            &generated
+                        }
+                    };
+                    let level = match generator.attempt_generate_level(word_list, config, &mut rng)
+                    {
+                        Ok(level) => level,
+                        Err(SolveOutcome::Unsolvable) if fixed_paths => {
+                            unsolvable.store(true, Ordering::Relaxed);
+                            break;
+                        }
+                        Err(_) => continue,
+                    };
+                    if !pred(&level) {
+                        continue;
+                    }
+
+                    let mut accepted = accepted.lock().unwrap();
+                    if accepted
+                        .as_ref()
+                        .is_none_or(|(index, _)| candidate < *index)
+                    {
+                        *accepted = Some((candidate, level));
+                    }
+                }
+            });
+        }
+    });
+
+    let Some((index, mut level)) = accepted.into_inner().unwrap() else {
+        return Err(match unsolvable.into_inner() {
+            true => GenerateError::Unsolvable,
+            false => GenerateError::TooManyCandidates(config.max_candidates),
+        });
+    };
+    level.seed = seed;
+
+    // Threads may have rejected candidates past the accepted one, which depend on
+    // timing, so only those before it are reported.
+    let mut rejected = rejected.into_inner().unwrap();
+    rejected.retain(|(candidate, _)| *candidate < index);
+    rejected.sort_by_key(|(candidate, _)| *candidate);
+    let rejections = rejected
+        .into_iter()
+        .map(|(_, rejection)| rejection)
+        .collect();
+    Ok((level, rejections))
+}
+
+/// A filter that returns true if the level's solution has the supplied
+/// minimum average letter count.
+pub fn has_minimum_avg_letter_count(level: &Level, size: usize) -> bool {
+    let letter_count = level
+        .words
+        .iter()
+        .fold(0, |count, word| count + word.chars().count());
+    let avg_count = letter_count / level.words.len();
+    avg_count >= size
+}
+
+/// Add available letters to this level to make it easier, and give more
+/// potential solutions to the user. This is done by sampling the suppplied
+/// letter frequencies. The level's tiles are recorded again to include them.
+pub fn increase_letters<R: Rng + ?Sized>(
+    level: &mut Level,
+    frequencies: &HashMap<char, usize>,
+    rng: &mut R,
+) {
+    let letter_count = level
+        .words
+        .iter()
+        .fold(0, |count, word| count + word.chars().count() - 2);
+
+    let mut freqs: Vec<_> = frequencies.iter().collect();
+    freqs.sort();
+    let choices: Vec<char> = freqs.iter().map(|i| i.0).copied().collect();
+    let weights: Vec<usize> = freqs.iter().map(|i| i.1).copied().collect();
+    let dist = WeightedIndex::new(&weights).unwrap();
+
+    let padded_word: String = (0..letter_count / 2)
+        .map(|_| choices[dist.sample(rng)])
+        .collect();
+    level.words.push(padded_word);
+    level.record_tiles();
+}
+
+/// Return the name of the level in YYYY-MM-DD format.
+pub fn level_name(start_date: &DateTime<Utc>, index: u64) -> String {
+    let Some(date) = start_date.checked_add_days(Days::new(index)) else {
+        return format!("{}", index);
+    };
+    format!("{}", date.format("%Y-%m-%d"))
+}
+
+/// Derives the seed of a level from its name (see `level_name`) and a secret salt.
+///
+/// Since the seed only depends on the date and salt, any day's level can be
+/// regenerated on its own. The salt stops players from predicting future levels.
+pub fn date_seed(name: &str, salt: &str) -> u64 {
+    // 64-bit FNV-1a, which unlike `DefaultHasher` is stable between releases.
+    const OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+    const PRIME: u64 = 0x100000001b3;
+
+    let bytes = salt.bytes().chain([0]).chain(name.bytes());
+    let hash = bytes.fold(OFFSET_BASIS, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(PRIME)
+    });
+
+    // Mix the hash, so neighbouring dates get very different seeds.
+    mix_seed(hash)
+}
+
+/// Returns the seed of the `index`th level of a batch named `name`. With a salt it
+/// is derived from the name alone (see `date_seed`), so doesn't depend on where the
+/// level falls in the batch. Otherwise it is `seed + index`.
+pub fn level_seed(seed: u64, salt: Option<&str>, name: &str, index: u64) -> u64 {
+    match salt {
+        Some(salt) => date_seed(name, salt),
+        None => seed.wrapping_add(index),
+    }
+}
+
+/// Creates the random number generator levels are generated with from a seed.
+///
+/// `StdRng` may change algorithm between releases of rand, so ChaCha8 is used
+/// instead, which is guaranteed to give the same numbers for the same seed. The
+/// seed is expanded here rather than with `seed_from_u64` for the same reason.
+pub fn level_rng(seed: u64) -> ChaCha8Rng {
+    let mut bytes = [0; 32];
+    let mut state = seed;
+    for chunk in bytes.chunks_exact_mut(8) {
+        state = mix_seed(state);
+        chunk.copy_from_slice(&state.to_le_bytes());
+    }
+    ChaCha8Rng::from_seed(bytes)
+}
+
+/// Derives the seed of a level's `index`th candidate from the level's seed.
+pub fn candidate_seed(seed: u64, index: u64) -> u64 {
+    mix_seed(seed ^ mix_seed(index))
+}
+
+/// The splitmix64 finaliser, which spreads small changes in the input across
+/// every bit of the output.
+fn mix_seed(seed: u64) -> u64 {
+    let mut z = seed.wrapping_add(0x9e3779b97f4a7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+    z ^ (z >> 31)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use generator::PathOptions;
+    use room::RoomRequirements;
+    use solver::{SolverConfig, SolverStrategy, WordWeighting};
+    use walls::{Symmetry, WallStyle};
+
+    const WORDS: &str = "ant arm art ate bat bed bee cat cot cup dog den ear eat egg end \
+        hat hen ink inn net nut oak oat owl pen pet pin pot rat red rot sea set sun tan \
+        tea ten tin toe top urn wet able area bake bare bone cake care cart dare dart \
+        date earn east edge gate hare heat late mate near neat note rate read seat side \
+        sore star tear tide tone trap tree wide acorn after alert apple earth enter eaten \
+        other raise rates react stare steer table tease three trade treat";
+
+    fn word_list() -> WordList {
+        WordList::from_words(WORDS.split_whitespace().map(String::from).collect())
+    }
+
+    fn config() -> GeneratorConfig {
+        GeneratorConfig {
+            rows: 6,
+            cols: 6,
+            solver: SolverConfig {
+                strategy: SolverStrategy::Backtracking,
+                weighting: WordWeighting::Permissive,
+                retries: 20,
+                node_budget: 1000,
+            },
+            paths: PathOptions {
+                extra_turns: 2,
+                extra_length: 2,
+                max_paths: 20,
+            },
+            walls: WallStyle::Noise,
+            symmetry: Symmetry::None,
+            // Reject some grids, so the accepted level isn't always the first
+            // candidate.
+            room: RoomRequirements {
+                min_free_fraction: 0.7,
+                ..Default::default()
+            },
+            max_candidates: 1000,
+            locked_letters: 1,
+            mask: None,
+            template: None,
+        }
+    }
+
+    #[test]
+    fn levels_do_not_depend_on_the_number_of_threads() {
+        let word_list = word_list();
+        let config = config();
+        for seed in 0..4 {
+            let generate = |threads| {
+                let (level, rejections) =
+                    generate_level(&word_list, &config, seed, threads, |level| {
+                        has_minimum_avg_letter_count(level, 4)
+                    })
+                    .unwrap();
+                (serde_json::to_string(&level).unwrap(), rejections)
+            };
+            let (level, rejections) = generate(1);
+            assert_eq!(generate(4), (level, rejections));
+        }
+    }
+
+    #[test]
+    fn date_seeds_stay_the_same() {
+        // Changing this breaks regenerating every published daily level.
+        assert_eq!(date_seed("2025-01-01", "salt"), 5446384356719807256);
+        assert_ne!(
+            date_seed("2025-01-01", "other"),
+            date_seed("2025-01-01", "salt")
+        );
+        assert_ne!(
+            date_seed("2025-01-02", "salt"),
+            date_seed("2025-01-01", "salt")
+        );
+    }
+
+    #[test]
+    fn a_date_regenerates_the_same_as_in_a_batch() {
+        let word_list = word_list();
+        let config = config();
+        let frequencies = word_list.frequencies();
+        // Generates and pads the levels of a batch the way the generate command
+        // does.
+        let generate = |start: &str, count: u64, salt: Option<&str>| -> Vec<String> {
+            let start = start.parse::<DateTime<Utc>>().unwrap();
+            (0..count)
+                .map(|i| {
+                    let seed = level_seed(7, salt, &level_name(&start, i), i);
+                    let (mut level, _) =
+                        generate_level(&word_list, &config, seed, 2, |_| true).unwrap();
+                    increase_letters(&mut level, &frequencies, &mut level_rng(seed));
+                    serde_json::to_string(&level).unwrap()
+                })
+                .collect()
+        };
+
+        let batch = generate("2025-01-01T00:00:00Z", 4, Some("salt"));
+        let alone = generate("2025-01-04T00:00:00Z", 1, Some("salt"));
+        assert_eq!(batch[3], alone[0]);
+
+        // Without a salt, levels depend on their place in the batch instead.
+        let batch = generate("2025-01-01T00:00:00Z", 4, None);
+        let alone = generate("2025-01-04T00:00:00Z", 1, None);
+        assert_ne!(batch[3], alone[0]);
+    }
+}
@@ -1,119 +1,480 @@
-use rand::distr::weighted::WeightedIndex;
-use rand::prelude::*;
-
-use std::{collections::HashMap, fs};
-
-use chrono::{DateTime, Days, TimeZone, Utc};
-use edge_map::EdgeMap;
-use game::{Grid, Level};
-use generator::LevelGenerator;
-use position::Direction;
-use solver::WordList;
-
-mod edge_map;
-mod game;
-mod generator;
-mod position;
-mod solver;
-
-type DistanceMap = EdgeMap<usize>;
-type TurnsMap = EdgeMap<(usize, Option<Direction>)>;
-
-const START_DATE: &str = "2025-05-03 12:12:12Z";
-const LEVEL_COUNT: usize = 10;
-const WORDS_PATH: &str = "assets/easy_words.json";
-const OUTPUT_FOLDER: &str = "assets/output";
-
-/// Generates a supplied amount of levels that satisfy the predicate function.
-fn generate_levels(
-    word_list: WordList,
-    amount: usize,
-    size: (usize, usize),
-    pred: fn(&Level) -> bool,
-) -> Vec<Level> {
-    let mut result: Vec<Level> = Vec::new();
-    let (rows, cols) = size;
-    while result.len() < amount {
-        let generator = LevelGenerator::from_grid(Grid::new(rows, cols));
-        if let Some(level) = generator.attempt_generate_level(&word_list, 20) {
-            if pred(&level) {
-                println!("Added level: {}", result.len());
-                result.push(level);
+use std::{
+    borrow::Cow,
+    collections::{BTreeMap, HashSet, VecDeque},
+    fs,
+    path::PathBuf,
+    process, thread,
+};
+
+use chrono::{Datelike, Days, NaiveTime, Utc};
+use clap::Parser;
+use cli::{Cli, Command, GenerateArgs, ShowArgs, StatsArgs, ValidateArgs, WordsArgs};
+use wc_rs::{
+    archive::{LevelArchive, load_level},
+    game::Level,
+    generate_level,
+    generator::{Dictionaries, GeneratorConfig, PathOptions},
+    has_minimum_avg_letter_count, increase_letters,
+    language::{Language, LetterFrequencies},
+    letter_bag::LetterBag,
+    level_name, level_rng, level_seed,
+    mask::Mask,
+    pattern::parse_pattern,
+    room::{RoomRejection, RoomRequirements},
+    solver::{SolverConfig, WordConstraint, WordList},
+    template::load_template,
+    validator::{PlacedWord, validate_solution},
+    words::read_frequency_file,
+};
+
+mod cli;
+
+/// Loads and merges the word lists, exiting with the reason if any can't be
+/// loaded. Rejected entries are summarised on stderr.
+fn load_words(paths: &[PathBuf], language: Language) -> WordList {
+    let (word_list, rejected) = match WordList::from_paths(paths, language) {
+        Ok(loaded) => loaded,
+        Err(error) => {
+            eprintln!("Could not load words: {}", error);
+            process::exit(1);
+        }
+    };
+    if !rejected.is_empty() {
+        eprintln!("Rejected {} word list entries:", rejected.len());
+        for entry in rejected.iter().take(10) {
+            eprintln!("  {}", entry);
+        }
+        if rejected.len() > 10 {
+            eprintln!("  ...");
+        }
+    }
+    word_list
+}
+
+/// Summarises the words players could build from the level's letters, and the
+/// longest obscure ones.
+fn print_alternate_words(dictionaries: &Dictionaries, level: &Level) {
+    let alternates = dictionaries.alternate_words(level);
+    let mut obscure = alternates.obscure.clone();
+    obscure.sort_by_key(|word| std::cmp::Reverse(word.chars().count()));
+    println!(
+        "Alternate words: {} familiar, {} obscure (longest: {})",
+        alternates.familiar.len(),
+        alternates.obscure.len(),
+        obscure
+            .iter()
+            .take(5)
+            .copied()
+            .collect::<Vec<_>>()
+            .join(", ")
+    );
+}
+
+/// Summarises why grids were rejected before the level was generated, grouped by
+/// reason.
+fn print_rejections(rejections: &[RoomRejection]) {
+    if rejections.is_empty() {
+        return;
+    }
+    let mut reasons: BTreeMap<&str, usize> = BTreeMap::new();
+    for rejection in rejections {
+        *reasons.entry(rejection.reason()).or_insert(0) += 1;
+    }
+    println!(
+        "Rejected {} grids: {}",
+        rejections.len(),
+        reasons
+            .iter()
+            .map(|(reason, count)| format!("{} {}", count, reason))
+            .collect::<Vec<_>>()
+            .join(", ")
+    );
+}
+
+/// Returns the solution words of a saved level, dropping the padding that
+/// `increase_letters` appends as its last word.
+fn saved_solution_words(mut words: Vec<String>) -> Vec<String> {
+    words.pop();
+    words
+}
+
+/// Reads the solution words of a saved level for the history. A level that can't
+/// be read is reported and left out, rather than stopping the batch.
+fn read_saved_words(archive: &LevelArchive, name: &str) -> Option<Vec<String>> {
+    match archive.read(name) {
+        Ok(level) => Some(saved_solution_words(level.words)),
+        Err(error) => {
+            eprintln!(
+                "Could not read saved level {}: {}",
+                archive.path(name).display(),
+                error
+            );
+            None
+        }
+    }
+}
+
+/// Adds a level's words to the history, forgetting the oldest level once it
+/// holds more than `days` levels.
+fn remember_words(history: &mut VecDeque<Vec<String>>, days: usize, words: Vec<String>) {
+    history.push_back(words);
+    while history.len() > days {
+        history.pop_front();
+    }
+}
+
+fn generate(args: GenerateArgs) {
+    if !matches!(args.max_rank_by_weekday.len(), 0 | 7) {
+        eprintln!("--max-rank-by-weekday needs a rank for each day of the week");
+        process::exit(1);
+    }
+    let mut generation_words = load_words(&args.word_list.words, args.word_list.language);
+    if let Some(path) = &args.frequencies {
+        match read_frequency_file(path, args.word_list.language) {
+            Ok(ranks) => generation_words = generation_words.with_ranks(&ranks),
+            Err(error) => {
+                eprintln!("Could not load word frequencies: {}", error);
+                process::exit(1);
+            }
+        }
+    }
+    let mut play_words = load_words(&args.play_words, args.word_list.language);
+    if !args.blocklist.is_empty() {
+        let blocked: HashSet<String> = load_words(&args.blocklist, args.word_list.language)
+            .words()
+            .map(|word| word.to_string())
+            .collect();
+        generation_words = generation_words.without(&blocked);
+        play_words = play_words.without(&blocked);
+    }
+    let (dictionaries, removed) = Dictionaries::new(generation_words, play_words);
+    if !removed.is_empty() {
+        eprintln!(
+            "Left out {} generation words that aren't in the play word lists",
+            removed.len()
+        );
+    }
+    let word_list = dictionaries.generation();
+    let frequencies = match args.letter_frequencies {
+        LetterFrequencies::WordList => word_list.frequencies(),
+        LetterFrequencies::Language => args.word_list.language.letter_frequencies(),
+    };
+    let archive = args.output.map(|dir| match LevelArchive::open(&dir) {
+        Ok(archive) => archive,
+        Err(error) => {
+            eprintln!(
+                "Could not create output folder {}: {}",
+                dir.display(),
+                error
+            );
+            process::exit(1);
+        }
+    });
+    let start_date = args
+        .start_date
+        .unwrap_or_else(|| Utc::now().date_naive())
+        .and_time(NaiveTime::MIN)
+        .and_utc();
+
+    let seed = args.seed.unwrap_or_else(rand::random);
+    match &args.salt {
+        Some(_) => println!("Seeds derived from level dates"),
+        None => println!("Seed: {}", seed),
+    }
+
+    let threads = args.threads.unwrap_or_else(|| {
+        thread::available_parallelism()
+            .map(|n| n.get())
+            .unwrap_or(1)
+    });
+
+    let config = GeneratorConfig {
+        rows: args.rows,
+        cols: args.cols,
+        solver: SolverConfig {
+            strategy: args.solver,
+            weighting: args.weighting,
+            retries: args.solver_retries,
+            node_budget: args.node_budget,
+        },
+        paths: PathOptions {
+            extra_turns: args.extra_turns,
+            extra_length: args.extra_length,
+            max_paths: args.max_paths,
+        },
+        walls: args.walls,
+        symmetry: args.symmetry,
+        room: RoomRequirements {
+            min_free_fraction: args.min_free_fraction,
+            min_room_size: args.min_room_size,
+            max_corridor_run: args.max_corridor,
+            fill_pockets: args.fill_pockets,
+        },
+        max_candidates: args.max_candidates,
+        locked_letters: args.locked_letters,
+        mask: match (&args.mask, args.shape) {
+            (Some(path), _) => match Mask::load(path) {
+                Ok(mask) => Some(mask),
+                Err(error) => {
+                    eprintln!("Could not load mask {}: {}", path.display(), error);
+                    process::exit(1);
+                }
+            },
+            (None, Some(shape)) => Some(Mask::shape(shape, args.rows, args.cols)),
+            (None, None) => None,
+        },
+        template: args
+            .template
+            .as_ref()
+            .map(|path| match load_template(path) {
+                Ok(template) => template,
+                Err(error) => {
+                    eprintln!("Could not load template {}: {}", path.display(), error);
+                    process::exit(1);
+                }
+            }),
+    };
+
+    // The solution words of the previous `--history` days, oldest first.
+    let mut history: VecDeque<Vec<String>> = VecDeque::new();
+    if let Some(archive) = &archive {
+        for days_before in (1..=args.history as u64).rev() {
+            let Some(date) = start_date.checked_sub_days(Days::new(days_before)) else {
+                continue;
+            };
+            let name = level_name(&date, 0);
+            if archive.contains(&name)
+                && let Some(words) = read_saved_words(archive, &name)
+            {
+                history.push_back(words);
             }
         }
     }
 
-    result
+    let mut written: Vec<String> = Vec::new();
+    let mut skipped: Vec<String> = Vec::new();
+    let min_length = args.min_avg_word_length;
+    for i in 0..args.count {
+        let name = level_name(&start_date, i as u64);
+        if let Some(archive) = &archive
+            && archive.contains(&name)
+        {
+            println!("Skipped level {}: already saved", name);
+            // The saved level is only needed for its words.
+            if args.history > 0
+                && let Some(words) = read_saved_words(archive, &name)
+            {
+                remember_words(&mut history, args.history, words);
+            }
+            skipped.push(name);
+            continue;
+        }
+
+        let level_seed = level_seed(seed, args.salt.as_deref(), &name, i as u64);
+        let mut level_words = Cow::Borrowed(word_list);
+        let date = start_date + Days::new(i as u64);
+        let max_rank = match args.max_rank_by_weekday.as_slice() {
+            [] => args.max_rank,
+            by_weekday => Some(by_weekday[date.weekday().num_days_from_monday() as usize])
+                .filter(|&max_rank| max_rank > 0),
+        };
+        if let Some(max_rank) = max_rank {
+            level_words = Cow::Owned(level_words.up_to_rank(max_rank));
+        }
+        let recent: HashSet<String> = history.iter().flatten().cloned().collect();
+        if !recent.is_empty() {
+            level_words = Cow::Owned(args.history_mode.apply(
+                &level_words,
+                &recent,
+                args.history_weight,
+            ));
+        }
+        let generated = generate_level(&level_words, &config, level_seed, threads, |level| {
+            has_minimum_avg_letter_count(level, min_length)
+        });
+        let (mut level, rejections) = match generated {
+            Ok(generated) => generated,
+            Err(error) => {
+                eprintln!("Could not generate level {}: {}", name, error);
+                process::exit(1);
+            }
+        };
+        let unpadded = dictionaries
+            .play()
+            .words_from_bag(&level.letters(), &[])
+            .len();
+        let solution_words = level.words.clone();
+        let mut rng = level_rng(level_seed);
+        increase_letters(&mut level, &frequencies, &mut rng);
+        println!("Level {} (seed {})", name, level_seed);
+        level.visualise();
+        print_rejections(&rejections);
+        print_alternate_words(&dictionaries, &level);
+        println!(
+            "Padding: {} -> {} playable words",
+            unpadded,
+            dictionaries
+                .play()
+                .words_from_bag(&level.letters(), &[])
+                .len()
+        );
+        remember_words(&mut history, args.history, solution_words);
+
+        if let Some(archive) = &archive {
+            let path = archive.write(&name, &level).expect("Couldn't write level");
+            println!("Saved {}", path.display());
+            written.push(name);
+        }
+    }
+
+    if archive.is_some() {
+        println!("Written ({}): {}", written.len(), written.join(", "));
+        println!("Skipped ({}): {}", skipped.len(), skipped.join(", "));
+    }
 }
 
-/// A filter that returns true if the level's solution has the supplied
-/// minimum average letter count.
-fn has_minimum_avg_letter_count<const SIZE: usize>(level: &Level) -> bool {
-    // Avg letter count must be greater than 3
-    let letter_count = level.words.iter().fold(0, |count, word| count + word.len());
-    let avg_count = letter_count / level.words.len();
-    return avg_count >= SIZE;
+fn show(args: ShowArgs) {
+    for path in args.paths {
+        if path.is_dir() {
+            let archive = LevelArchive::open(&path).expect("Could not open level folder");
+            for name in archive.names().expect("Could not list levels") {
+                let level = archive.read(&name).expect("Could not read level");
+                println!("Level {} (seed {})", name, level.seed);
+                level.visualise();
+            }
+        } else {
+            let level = load_level(&path).expect("Could not read level");
+            println!("Level {} (seed {})", path.display(), level.seed);
+            level.visualise();
+        }
+    }
 }
 
-/// Add available letters to this level to make it easier, and give more
-/// potential solutions to the user. This is done by sampling the suppplied
-/// letter frequencies.
-fn increase_letters(level: &mut Level, frequencies: &HashMap<char, usize>) {
-    let letter_count = level
-        .words
-        .iter()
-        .fold(0, |count, word| count + word.len() - 2);
-
-    let freqs: Vec<_> = frequencies.iter().collect();
-    let choices: Vec<char> = freqs.iter().map(|i| i.0).copied().collect();
-    let weights: Vec<usize> = freqs.iter().map(|i| i.1).copied().collect();
-    let dist = WeightedIndex::new(&weights).unwrap();
-    let mut rng = rand::rng();
-
-    let mut padded_word = String::new();
-    while padded_word.len() < letter_count / 2 {
-        padded_word.push(choices[dist.sample(&mut rng)])
-    }
-    level.words.push(padded_word);
+fn validate(args: ValidateArgs) {
+    let level = load_level(&args.level).expect("Could not read level");
+    let word_list = load_words(&args.word_list.words, args.word_list.language);
+    let raw = fs::read_to_string(&args.solution).expect("Could not read solution");
+    let words: Vec<PlacedWord> = serde_json::from_str(&raw).expect("Could not parse solution");
+
+    match validate_solution(&level, &words, &word_list, args.word_list.language) {
+        Ok(solution) => println!(
+            "Valid: {} words using {} letters",
+            solution.word_count, solution.letters_used
+        ),
+        Err(error) => {
+            println!("Invalid: {}", error);
+            process::exit(1);
+        }
+    }
 }
 
-/// Return the name of the level in YYYY-MM-DD format.
-fn level_name(start_date: &DateTime<Utc>, index: u64) -> String {
-    let Some(date) = start_date.checked_add_days(Days::new(index)) else {
-        return format!("{}", index);
+fn stats(args: StatsArgs) {
+    let word_list = load_words(&args.word_list.words, args.word_list.language);
+    println!("Words: {}", word_list.size());
+
+    println!("By length:");
+    for (length, count) in word_list.length_counts() {
+        println!("  {:>2}: {}", length, count);
+    }
+
+    let mut frequencies: Vec<_> = word_list.frequencies().into_iter().collect();
+    frequencies.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+    println!("Letter frequencies:");
+    for (letter, count) in frequencies {
+        println!("  {}: {}", letter, count);
+    }
+}
+
+/// Normalises letters given on the command line like the words of the word list,
+/// exiting if any aren't in the language's alphabet.
+fn normalise_letters(letters: &str, language: Language) -> String {
+    match language.normalise(letters) {
+        Some(letters) => letters,
+        None => {
+            eprintln!("{:?} has letters outside the alphabet", letters);
+            process::exit(1);
+        }
+    }
+}
+
+fn words(args: WordsArgs) {
+    let mut constraints = match &args.pattern {
+        Some(pattern) => match parse_pattern(pattern) {
+            Ok(constraints) => constraints,
+            Err(error) => {
+                eprintln!("Invalid pattern: {}", error);
+                process::exit(1);
+            }
+        },
+        None => args
+            .length
+            .map(WordConstraint::Length)
+            .into_iter()
+            .collect(),
     };
-    format!("{}", date.format("%Y-%m-%d"))
+    let language = args.word_list.language;
+    for letters in &args.contains {
+        constraints.push(WordConstraint::Contains(normalise_letters(
+            letters, language,
+        )));
+    }
+
+    let word_list = load_words(&args.word_list.words, args.word_list.language);
+    // Letters required by the pattern are on the board, and don't come from the
+    // bag.
+    let bag = args
+        .letters
+        .as_ref()
+        .map(|letters| LetterBag::from_words([normalise_letters(letters, language).as_str()]));
+    let result = word_list.query(&constraints, bag.as_ref(), args.limit);
+    println!("Matches: {}", result.count);
+    for word in result.sample {
+        println!("  {}", word);
+    }
+    if result.count > args.limit {
+        println!("  ...");
+    }
 }
 
 fn main() {
-    let word_list = WordList::from_path(WORDS_PATH).expect("Could not load words");
-    let frequencies = word_list.frequencies();
-
-    // Create the levels
-    let mut levels = generate_levels(
-        word_list,
-        LEVEL_COUNT,
-        (8, 8),
-        has_minimum_avg_letter_count::<4>,
-    );
-    levels
-        .iter_mut()
-        .for_each(|level| increase_letters(level, &frequencies));
-
-    levels.iter().for_each(Level::visualise);
-
-    // NOTE: The code below is only relevant for saving levels to disk.
-    // If you just want to visualise them, leave it commented out.
-    //
-    // Build their names and save them to disk
-    //let start_date = START_DATE.parse::<DateTime<Utc>>().unwrap();
-    //
-    //levels.iter().enumerate().for_each(|(i, level)| {
-    //    let raw = serde_json::to_string(level).expect("Couldn't convert level");
-    //    let name = level_name(&start_date, i as u64);
-    //    let path = format!("{}/{}.json", OUTPUT_FOLDER, name);
-    //    println!("{}", path);
-    //
-    //    fs::write(path, raw).expect("Couldn't write.");
-    //});
+    let cli = Cli::parse();
+    match cli.command {
+        Command::Generate(args) => generate(*args),
+        Command::Show(args) => show(args),
+        Command::Validate(args) => validate(args),
+        Command::Stats(args) => stats(args),
+        Command::Words(args) => words(args),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wc_rs::{game::Grid, position::Position};
+
+    #[test]
+    fn padding_is_not_a_solution_word() {
+        let mut level = Level {
+            start: Position::new(0, 0),
+            goal: Position::new(0, 2),
+            grid: Grid::new(1, 3),
+            words: vec!["cat".to_string()],
+            path: Vec::new(),
+            seed: 0,
+            tiles: None,
+        };
+        let frequencies = [('z', 1)].into_iter().collect();
+        increase_letters(&mut level, &frequencies, &mut level_rng(0));
+        assert_eq!(level.words.len(), 2);
+        assert_eq!(saved_solution_words(level.words), vec!["cat"]);
+    }
+
+    #[test]
+    fn history_keeps_the_most_recent_days() {
+        let mut history = VecDeque::new();
+        for word in ["cat", "dog", "hen"] {
+            remember_words(&mut history, 2, vec![word.to_string()]);
+        }
+        assert_eq!(history, [vec!["dog"], vec!["hen"]]);
+    }
 }
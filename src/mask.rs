@@ -0,0 +1,203 @@
+use clap::ValueEnum;
+use std::{collections::HashSet, fmt, fs, io, path::Path};
+
+use crate::position::Position;
+
+/// The outline of a board, marking which squares of its `rows x cols` rectangle
+/// are part of it. Squares outside the mask are void.
+#[derive(Debug, Clone)]
+pub struct Mask {
+    pub rows: usize,
+    pub cols: usize,
+    inside: HashSet<Position>,
+}
+
+/// The built in board outlines, drawn to fit the grid's rows and columns.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Shape {
+    Heart,
+    Diamond,
+    Circle,
+}
+
+#[derive(Debug)]
+pub enum MaskError {
+    FileError(io::Error),
+    Empty,
+}
+
+impl fmt::Display for MaskError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MaskError::FileError(error) => write!(f, "could not read mask: {}", error),
+            MaskError::Empty => write!(f, "the mask has no squares"),
+        }
+    }
+}
+
+impl std::error::Error for MaskError {}
+
+impl Mask {
+    /// Draws the shape to fill a grid of the given size.
+    pub fn shape(shape: Shape, rows: usize, cols: usize) -> Self {
+        let mut inside = HashSet::new();
+        for row in 0..rows {
+            for col in 0..cols {
+                // Measure from the nearest edge, so the shape is exactly symmetric
+                // left to right.
+                let mirrored = col.min(cols - 1 - col);
+                let x = (mirrored as f64 + 0.5) / cols as f64 * 2.0 - 1.0;
+                let y = 1.0 - (row as f64 + 0.5) / rows as f64 * 2.0;
+                let contains = match shape {
+                    Shape::Circle => x * x + y * y <= 1.0,
+                    Shape::Diamond => x.abs() + y.abs() <= 1.0,
+                    Shape::Heart => {
+                        // The heart curve spans roughly -1.2..1.2 across and -1..1.2
+                        // down. Its top is cropped a little, so the notch between
+                        // the lobes shows on small grids.
+                        let (x, y) = (x * 1.2, y * 1.1 + 0.2);
+                        (x * x + y * y - 1.0).powi(3) - x * x * y.powi(3) <= 0.0
+                    }
+                };
+                if contains {
+                    inside.insert(Position::new(row as isize, col as isize));
+                }
+            }
+        }
+        Self { rows, cols, inside }
+    }
+
+    /// Reads a mask from a file, see `parse`.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, MaskError> {
+        let text = fs::read_to_string(path).map_err(MaskError::FileError)?;
+        Self::parse(&text)
+    }
+
+    /// Parses a mask drawn one row per line, where spaces and `~` are outside the
+    /// board and any other character is inside it. The mask is as wide as its
+    /// longest line.
+    pub fn parse(text: &str) -> Result<Self, MaskError> {
+        let lines: Vec<&str> = text.lines().collect();
+        let rows = lines.len();
+        let cols = lines
+            .iter()
+            .map(|line| line.chars().count())
+            .max()
+            .unwrap_or(0);
+
+        let mut inside = HashSet::new();
+        for (row, line) in lines.into_iter().enumerate() {
+            for (col, c) in line.chars().enumerate() {
+                if !c.is_whitespace() && c != '~' {
+                    inside.insert(Position::new(row as isize, col as isize));
+                }
+            }
+        }
+        if inside.is_empty() {
+            return Err(MaskError::Empty);
+        }
+        Ok(Self { rows, cols, inside })
+    }
+
+    /// Returns true if the position is part of the board.
+    pub fn contains(&self, position: Position) -> bool {
+        self.inside.contains(&position)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        game::{Entity, Grid},
+        generator::{LevelGenerator, PathOptions},
+        level_rng,
+        room::RoomRequirements,
+        solver::segment_positions,
+        walls::{Symmetry, WallStyle},
+    };
+
+    #[test]
+    fn parses_void_and_ragged_lines() {
+        let mask = Mask::parse("~##\n# ~#\n#").unwrap();
+        assert_eq!((mask.rows, mask.cols), (3, 4));
+        assert!(!mask.contains(Position::new(0, 0)));
+        assert!(mask.contains(Position::new(0, 1)));
+        // A space and `~` are both outside the board.
+        assert!(!mask.contains(Position::new(1, 1)));
+        assert!(!mask.contains(Position::new(1, 2)));
+        assert!(mask.contains(Position::new(1, 3)));
+        // Short lines are padded with void.
+        assert!(!mask.contains(Position::new(0, 3)));
+        assert!(mask.contains(Position::new(2, 0)));
+        assert!(!mask.contains(Position::new(2, 1)));
+    }
+
+    #[test]
+    fn rejects_masks_without_squares() {
+        assert!(matches!(Mask::parse(""), Err(MaskError::Empty)));
+        assert!(matches!(Mask::parse("~~ \n  ~"), Err(MaskError::Empty)));
+    }
+
+    #[test]
+    fn shapes_are_symmetric_and_fit_the_grid() {
+        for &shape in Shape::value_variants() {
+            let mask = Mask::shape(shape, 9, 10);
+            assert_eq!((mask.rows, mask.cols), (9, 10));
+            assert!(mask.contains(Position::new(4, 4)), "{:?}", shape);
+            assert!(!mask.contains(Position::new(0, 0)), "{:?}", shape);
+            for position in &mask.inside {
+                assert!(position.is_within_bounds(9, 10));
+                let mirrored = Position::new(position.row, 9 - position.col);
+                assert!(mask.contains(mirrored), "{:?} {:?}", shape, position);
+            }
+        }
+    }
+
+    #[test]
+    fn nothing_is_placed_on_void_squares() {
+        let mask = Mask::shape(Shape::Heart, 9, 10);
+        let options = PathOptions {
+            extra_turns: 1,
+            extra_length: 2,
+            max_paths: 5,
+        };
+        let mut checked = 0;
+        for &style in WallStyle::value_variants() {
+            let strategy = style.strategy();
+            for seed in 0..5 {
+                let mut rng = level_rng(seed);
+                let Ok(generator) = LevelGenerator::from_grid(
+                    Grid::from_mask(&mask),
+                    strategy.as_ref(),
+                    Symmetry::None,
+                    &RoomRequirements::default(),
+                    &mut rng,
+                ) else {
+                    continue;
+                };
+                checked += 1;
+                let grid = &generator.grid;
+                for row in 0..grid.rows {
+                    for col in 0..grid.cols {
+                        let position = Position::new(row as isize, col as isize);
+                        if !mask.contains(position) {
+                            assert_eq!(grid.entities.get(&position), Some(&Entity::Void));
+                            assert!(!generator.free_space.contains(&position));
+                        }
+                    }
+                }
+
+                let (start, goal) = generator.choose_start_and_goal(&mut rng).unwrap();
+                for path in generator.find_paths(start, goal, &options) {
+                    for pair in path.windows(2) {
+                        for position in segment_positions((pair[0], pair[1])) {
+                            assert!(mask.contains(position), "{:?} {}", style, seed);
+                        }
+                    }
+                }
+            }
+        }
+        assert!(checked > 0);
+    }
+}
@@ -0,0 +1,120 @@
+use std::fmt;
+
+use crate::solver::WordConstraint;
+
+/// The ways a word pattern can be malformed. Positions are the (0-based)
+/// character index in the pattern.
+#[derive(PartialEq, Eq, Debug)]
+pub enum PatternError {
+    UnclosedSet(usize),
+    EmptySet(usize),
+    InvalidCharacter(usize, char),
+}
+
+impl fmt::Display for PatternError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PatternError::UnclosedSet(i) => write!(f, "the set opened at {} is never closed", i),
+            PatternError::EmptySet(i) => write!(f, "the set at {} has no letters", i),
+            PatternError::InvalidCharacter(i, c) => {
+                write!(f, "{:?} at {} is not a letter, '?' or a set", c, i)
+            }
+        }
+    }
+}
+
+impl std::error::Error for PatternError {}
+
+/// Parses a pattern describing words of a fixed length, one square at a time:
+/// - `?` matches any letter.
+/// - A letter matches only that letter, ignoring case.
+/// - `[abc]` matches any of the letters in the brackets.
+///
+/// So `?a??s` matches five letter words with `a` second and ending in `s`, and
+/// `[bc]??` matches three letter words starting with `b` or `c`. Letters that may
+/// appear anywhere in a word aren't part of the pattern, and are given with
+/// `WordConstraint::Contains` instead.
+pub fn parse_pattern(pattern: &str) -> Result<Vec<WordConstraint>, PatternError> {
+    let mut constraints = Vec::new();
+    let mut index = 0;
+    let pattern = pattern.to_lowercase();
+    let mut chars = pattern.chars().enumerate();
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '?' => {}
+            '[' => {
+                let mut letters = Vec::new();
+                loop {
+                    match chars.next() {
+                        Some((_, ']')) => break,
+                        Some((_, letter)) if letter.is_alphabetic() => {
+                            if !letters.contains(&letter) {
+                                letters.push(letter);
+                            }
+                        }
+                        Some((j, other)) => return Err(PatternError::InvalidCharacter(j, other)),
+                        None => return Err(PatternError::UnclosedSet(i)),
+                    }
+                }
+                match letters.as_slice() {
+                    [] => return Err(PatternError::EmptySet(i)),
+                    [letter] => constraints.push(WordConstraint::CharAt(index, *letter)),
+                    _ => {
+                        letters.sort();
+                        constraints.push(WordConstraint::AnyOf(index, letters));
+                    }
+                }
+            }
+            letter if letter.is_alphabetic() => {
+                constraints.push(WordConstraint::CharAt(index, letter));
+            }
+            other => return Err(PatternError::InvalidCharacter(i, other)),
+        }
+        index += 1;
+    }
+    constraints.insert(0, WordConstraint::Length(index));
+    Ok(constraints)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_letters_and_wildcards() {
+        assert_eq!(
+            parse_pattern("?A??s"),
+            Ok(vec![
+                WordConstraint::Length(5),
+                WordConstraint::CharAt(1, 'a'),
+                WordConstraint::CharAt(4, 's'),
+            ])
+        );
+    }
+
+    #[test]
+    fn parses_sets() {
+        assert_eq!(
+            parse_pattern("[cbc]?[d]"),
+            Ok(vec![
+                WordConstraint::Length(3),
+                WordConstraint::AnyOf(0, vec!['b', 'c']),
+                WordConstraint::CharAt(2, 'd'),
+            ])
+        );
+    }
+
+    #[test]
+    fn rejects_malformed_patterns() {
+        assert_eq!(parse_pattern("a[bc"), Err(PatternError::UnclosedSet(1)));
+        assert_eq!(parse_pattern("?[]"), Err(PatternError::EmptySet(1)));
+        assert_eq!(
+            parse_pattern("a1"),
+            Err(PatternError::InvalidCharacter(1, '1'))
+        );
+        assert_eq!(
+            parse_pattern("[a?]"),
+            Err(PatternError::InvalidCharacter(2, '?'))
+        );
+    }
+}
@@ -13,19 +13,25 @@ impl Position {
         Position { row, col }
     }
 
-    pub fn to_key(&self) -> String {
+    pub fn to_key(self) -> String {
         let mut res = self.row.to_string();
         res.push('_');
         res += self.col.to_string().as_str();
         res
     }
 
+    /// Parses a key in the `row_col` format produced by `to_key`.
+    pub fn from_key(key: &str) -> Option<Position> {
+        let (row, col) = key.split_once('_')?;
+        Some(Position::new(row.parse().ok()?, col.parse().ok()?))
+    }
+
     pub fn is_within_bounds(&self, rows: isize, cols: isize) -> bool {
         self.row >= 0 && self.row < rows && self.col >= 0 && self.col < cols
     }
 
     pub fn step_in_direction(&self, direction: Direction) -> Position {
-        self.clone() + direction_delta(direction)
+        *self + direction_delta(direction)
     }
 
     pub fn manhattan_distance(&self, position: Position) -> usize {
@@ -33,17 +39,14 @@ impl Position {
     }
 
     pub fn direction_to_position(&self, position: Position) -> Option<Direction> {
-        for direction in vec![
+        [
             Direction::Up,
             Direction::Down,
             Direction::Left,
             Direction::Right,
-        ] {
-            if self.step_in_direction(direction) == position {
-                return Some(direction);
-            }
-        }
-        None
+        ]
+        .into_iter()
+        .find(|&direction| self.step_in_direction(direction) == position)
     }
 
     pub fn neighbours(&self) -> Vec<Position> {
@@ -55,7 +58,7 @@ impl Position {
         ];
         directions
             .iter()
-            .map(|d| self.step_in_direction(d.clone()))
+            .map(|&d| self.step_in_direction(d))
             .collect()
     }
 }
@@ -72,7 +75,7 @@ impl ops::Sub<Position> for Position {
     type Output = Position;
 
     fn sub(self, rhs: Position) -> Self::Output {
-        return self + (-rhs);
+        self + (-rhs)
     }
 }
 
@@ -84,7 +87,7 @@ impl ops::Neg for Position {
     }
 }
 
-#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+#[derive(PartialEq, Eq, Clone, Copy, Hash, Debug)]
 pub enum Direction {
     Up,
     Down,
@@ -92,6 +95,17 @@ pub enum Direction {
     Right,
 }
 
+impl Direction {
+    pub fn opposite(self) -> Direction {
+        match self {
+            Direction::Up => Direction::Down,
+            Direction::Down => Direction::Up,
+            Direction::Left => Direction::Right,
+            Direction::Right => Direction::Left,
+        }
+    }
+}
+
 fn direction_delta(direction: Direction) -> Position {
     match direction {
         Direction::Up => Position::new(-1, 0),
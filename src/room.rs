@@ -0,0 +1,257 @@
+use std::{collections::HashSet, fmt};
+
+use crate::{
+    game::{Entity, Grid, sorted_positions},
+    position::Position,
+    walls::Symmetry,
+};
+
+/// Guarantees on the room a level is placed in, checked once the walls are
+/// placed. The defaults accept any room.
+#[derive(Debug, Clone, Default)]
+pub struct RoomRequirements {
+    /// The least share of the board's squares, in 0..1, that the room must cover.
+    pub min_free_fraction: f64,
+    /// The fewest squares the room may have.
+    pub min_room_size: usize,
+    /// The most squares a one-wide corridor may run for, if limited.
+    pub max_corridor_run: Option<usize>,
+    /// Whether to wall off single squares poking out of the room, which can only
+    /// be entered from one side.
+    pub fill_pockets: bool,
+}
+
+/// Why a grid's room was rejected.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RoomRejection {
+    TooLittleFreeSpace { free: usize, total: usize },
+    RoomTooSmall { size: usize },
+    CorridorTooLong { length: usize },
+}
+
+impl RoomRejection {
+    /// A short description of the reason, without the measurements, for grouping
+    /// rejections together.
+    pub fn reason(&self) -> &'static str {
+        match self {
+            RoomRejection::TooLittleFreeSpace { .. } => "too little free space",
+            RoomRejection::RoomTooSmall { .. } => "room too small",
+            RoomRejection::CorridorTooLong { .. } => "corridor too long",
+        }
+    }
+}
+
+impl fmt::Display for RoomRejection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RoomRejection::TooLittleFreeSpace { free, total } => {
+                write!(f, "only {} of {} squares are free", free, total)
+            }
+            RoomRejection::RoomTooSmall { size } => write!(f, "the room has {} squares", size),
+            RoomRejection::CorridorTooLong { length } => {
+                write!(f, "a corridor runs for {} squares", length)
+            }
+        }
+    }
+}
+
+impl RoomRequirements {
+    /// Repairs what it can of the grid's room, then checks the requirements,
+    /// returning the room's free space if they are met. Repairs respect the
+    /// symmetry, so a symmetric grid stays symmetric, and leave a single room.
+    pub fn enforce(
+        &self,
+        grid: &mut Grid,
+        symmetry: Symmetry,
+    ) -> Result<HashSet<Position>, RoomRejection> {
+        if self.fill_pockets {
+            fill_pockets(grid);
+        }
+
+        let free_space = grid.keep_largest_room(symmetry);
+        let total = grid.all_positions().len();
+        if (free_space.len() as f64) < self.min_free_fraction * total as f64 {
+            return Err(RoomRejection::TooLittleFreeSpace {
+                free: free_space.len(),
+                total,
+            });
+        }
+        if free_space.len() < self.min_room_size {
+            return Err(RoomRejection::RoomTooSmall {
+                size: free_space.len(),
+            });
+        }
+        if let Some(max_run) = self.max_corridor_run {
+            let length = longest_corridor(grid, &free_space);
+            if length > max_run {
+                return Err(RoomRejection::CorridorTooLong { length });
+            }
+        }
+        Ok(free_space)
+    }
+}
+
+/// Walls off every free square that can only be entered from a neighbour with at
+/// least three free neighbours of its own. Dead ends at the end of a corridor are
+/// left alone. On a symmetric grid, the images of a pocket are pockets too, so
+/// the grid stays symmetric.
+fn fill_pockets(grid: &mut Grid) {
+    loop {
+        let free_space = grid.free_space();
+        let pockets: Vec<Position> = sorted_positions(&free_space)
+            .into_iter()
+            .filter(
+                |p| match free_neighbours(grid, &free_space, *p).as_slice() {
+                    [entrance] => free_neighbours(grid, &free_space, *entrance).len() >= 3,
+                    _ => false,
+                },
+            )
+            .collect();
+        if pockets.is_empty() {
+            return;
+        }
+        grid.set_positions(pockets, Entity::Wall);
+    }
+}
+
+/// Returns the number of squares in the longest corridor of the free space, where
+/// a corridor is a connected run of squares with at most two free neighbours that
+/// aren't part of any free 2x2 block.
+fn longest_corridor(grid: &Grid, free_space: &HashSet<Position>) -> usize {
+    let corridor: HashSet<Position> = free_space
+        .iter()
+        .copied()
+        .filter(|p| free_neighbours(grid, free_space, *p).len() <= 2)
+        .filter(|p| !in_open_block(free_space, *p))
+        .collect();
+
+    let mut seen: HashSet<Position> = HashSet::new();
+    let mut longest = 0;
+    for start in sorted_positions(&corridor) {
+        if !seen.insert(start) {
+            continue;
+        }
+        let mut length = 0;
+        let mut queue = vec![start];
+        while let Some(position) = queue.pop() {
+            length += 1;
+            for neighbour in grid.valid_neighbours(position) {
+                if corridor.contains(&neighbour) && seen.insert(neighbour) {
+                    queue.push(neighbour);
+                }
+            }
+        }
+        longest = longest.max(length);
+    }
+    longest
+}
+
+fn free_neighbours(
+    grid: &Grid,
+    free_space: &HashSet<Position>,
+    position: Position,
+) -> Vec<Position> {
+    grid.valid_neighbours(position)
+        .into_iter()
+        .filter(|p| free_space.contains(p))
+        .collect()
+}
+
+/// Returns true if the position is a corner of a 2x2 block of free squares.
+fn in_open_block(free_space: &HashSet<Position>, position: Position) -> bool {
+    [(-1, -1), (-1, 1), (1, -1), (1, 1)]
+        .into_iter()
+        .any(|(row, col)| {
+            [
+                Position::new(row, 0),
+                Position::new(0, col),
+                Position::new(row, col),
+            ]
+            .into_iter()
+            .all(|offset| free_space.contains(&(position + offset)))
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        level_rng,
+        mask::{Mask, Shape},
+        walls::WallStyle,
+    };
+
+    #[test]
+    fn filling_pockets_leaves_a_single_room() {
+        let requirements = RoomRequirements {
+            fill_pockets: true,
+            ..Default::default()
+        };
+        let strategy = WallStyle::Noise.strategy();
+        // The heart isn't symmetric top to bottom, so images of a pocket
+        // aren't always pockets themselves.
+        let mask = Mask::shape(Shape::Heart, 10, 10);
+        for symmetry in [
+            Symmetry::Horizontal,
+            Symmetry::Rotational,
+            Symmetry::FourWay,
+        ] {
+            for seed in 0..10 {
+                let mut grid = Grid::from_mask(&mask);
+                grid.initialise_walls(strategy.as_ref(), symmetry, &mut level_rng(seed));
+                let Ok(room) = requirements.enforce(&mut grid, symmetry) else {
+                    panic!("{:?} {}: the room was rejected", symmetry, seed);
+                };
+                assert_eq!(grid.find_connected_sections().len(), 1);
+                assert_eq!(grid.explore_section(sorted_positions(&room)[0]), room);
+                for position in room.iter() {
+                    let neighbours = free_neighbours(&grid, &room, *position);
+                    if let [entrance] = neighbours.as_slice() {
+                        assert!(free_neighbours(&grid, &room, *entrance).len() < 3);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn rejects_rooms_that_fall_short() {
+        // A single corridor five squares long.
+        let mut grid = Grid::new(3, 5);
+        let walls = (0..5)
+            .flat_map(|col| [Position::new(0, col), Position::new(2, col)])
+            .collect();
+        grid.set_positions(walls, Entity::Wall);
+
+        let requirements = RoomRequirements {
+            min_room_size: 6,
+            ..Default::default()
+        };
+        assert_eq!(
+            requirements.enforce(&mut grid.clone(), Symmetry::None),
+            Err(RoomRejection::RoomTooSmall { size: 5 })
+        );
+        let requirements = RoomRequirements {
+            min_free_fraction: 0.5,
+            ..Default::default()
+        };
+        assert_eq!(
+            requirements.enforce(&mut grid.clone(), Symmetry::None),
+            Err(RoomRejection::TooLittleFreeSpace { free: 5, total: 15 })
+        );
+        let requirements = RoomRequirements {
+            max_corridor_run: Some(4),
+            ..Default::default()
+        };
+        assert_eq!(
+            requirements.enforce(&mut grid.clone(), Symmetry::None),
+            Err(RoomRejection::CorridorTooLong { length: 5 })
+        );
+        assert_eq!(
+            RoomRequirements::default()
+                .enforce(&mut grid, Symmetry::None)
+                .map(|room| room.len()),
+            Ok(5)
+        );
+    }
+}
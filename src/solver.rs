@@ -1,118 +1,399 @@
-use rand::seq::IteratorRandom;
-use serde_json;
+use clap::ValueEnum;
+use rand::Rng;
 use std::{
     collections::{HashMap, HashSet},
-    fs,
+    path::Path,
 };
 
-use crate::position::Position;
-
-// NOTE: Could make the dictionary a map of lengths to tries.
+use crate::{
+    csp,
+    language::Language,
+    letter_bag::LetterBag,
+    position::Position,
+    words::{LoadWordsError, RejectedWord, read_word_files},
+};
 
-#[derive(Debug)]
-pub enum LoadWordsError {
-    FileError,
-    ParseError,
+/// A dictionary of words, indexed for the lookups the solver makes.
+///
+/// Words are stored once, in sorted order, and the indexes hold the positions of
+/// words in that list, so lookups never clone words and always return them in the
+/// same order.
+#[derive(Clone)]
+pub struct WordList {
+    words: Vec<String>,
+    /// Word length -> words of that length.
+    by_length: HashMap<usize, Vec<usize>>,
+    /// (Word length, index, letter) -> words of that length with the letter at the
+    /// index. This covers first letter lookups at index 0, and last letter lookups
+    /// at index `length - 1`.
+    by_letter: HashMap<(usize, usize, char), Vec<usize>>,
+    /// How likely the solver is to choose each word, relative to the rest. Every
+    /// word starts with a weight of 1.
+    weights: Vec<f64>,
+    /// How common each word is, where rank 1 is the most common word. Words
+    /// without a rank are rarer than every ranked word.
+    ranks: Vec<Option<usize>>,
+    /// The rank given to words without one, just rarer than the rarest ranked word.
+    unranked: usize,
 }
 
-pub struct WordList(HashMap<usize, HashSet<String>>);
-
 impl WordList {
     pub fn from_words(words: Vec<String>) -> Self {
-        WordList(words.iter().fold(HashMap::new(), |mut res, word| {
-            if !res.contains_key(&word.len()) {
-                res.insert(word.len(), HashSet::new());
+        let mut words: Vec<String> = words.iter().map(|word| word.to_lowercase()).collect();
+        words.sort();
+        words.dedup();
+        let weights = vec![1.0; words.len()];
+        let ranks = vec![None; words.len()];
+        Self::from_sorted(words, weights, ranks)
+    }
+
+    /// Builds the indexes over words that are already sorted and deduplicated.
+    fn from_sorted(words: Vec<String>, weights: Vec<f64>, ranks: Vec<Option<usize>>) -> Self {
+        let mut by_length: HashMap<usize, Vec<usize>> = HashMap::new();
+        let mut by_letter: HashMap<(usize, usize, char), Vec<usize>> = HashMap::new();
+        for (id, word) in words.iter().enumerate() {
+            let length = word.chars().count();
+            by_length.entry(length).or_default().push(id);
+            for (index, letter) in word.chars().enumerate() {
+                by_letter
+                    .entry((length, index, letter))
+                    .or_default()
+                    .push(id);
             }
-            let set = res
-                .get_mut(&word.len())
-                .expect("Expected new set to be in result");
-            set.insert(word.to_lowercase());
-            res
-        }))
+        }
+
+        WordList {
+            words,
+            by_length,
+            by_letter,
+            weights,
+            unranked: ranks.iter().flatten().max().map_or(1, |rank| rank + 1),
+            ranks,
+        }
     }
 
     pub fn size(&self) -> usize {
-        self.0.iter().fold(0, |r, (_, s)| r + s.len())
+        self.words.len()
     }
 
-    pub fn from_path(path: &str) -> Result<WordList, LoadWordsError> {
-        fs::read_to_string(path)
-            .map_err(|_| LoadWordsError::FileError)
-            .and_then(|raw| {
-                serde_json::from_str::<Vec<String>>(raw.as_str())
-                    .map_err(|_| LoadWordsError::ParseError)
-            })
-            .map(WordList::from_words)
+    /// Returns every word in the list, in sorted order.
+    pub fn words(&self) -> impl Iterator<Item = &str> {
+        self.words.iter().map(|word| word.as_str())
     }
 
-    pub fn is_word_valid(&self, word: &String) -> bool {
-        return self
-            .0
-            .get(&word.len())
-            .map(|set| set.contains(word))
-            .unwrap_or(false);
+    /// Returns a word list of the words that are in both lists.
+    pub fn intersection(&self, other: &WordList) -> WordList {
+        self.filter(|word| other.is_word_valid(word))
     }
 
-    pub fn frequencies(&self) -> HashMap<char, usize> {
-        self.0.values().fold(HashMap::new(), |mut res, set| {
-            for word in set.into_iter() {
-                for letter in word.chars() {
-                    let current = res.get(&letter).unwrap_or(&0);
-                    res.insert(letter, current + 1);
-                }
-            }
-            res
-        })
+    /// Returns a word list without any of the supplied words.
+    pub fn without(&self, words: &HashSet<String>) -> WordList {
+        self.filter(|word| !words.contains(word))
     }
 
-    pub fn find_constrained_words(&self, constraints: Vec<WordConstraint>) -> HashSet<String> {
-        let max_index = constraints
+    /// Returns a copy of the word list where the supplied words are `factor` times
+    /// as likely to be chosen.
+    pub fn down_weighted(&self, words: &HashSet<String>, factor: f64) -> WordList {
+        let weights = self
+            .words
             .iter()
-            .map(|c| match c {
-                WordConstraint::Length(index) => index,
-                WordConstraint::CharAt(index, _) => index,
+            .zip(&self.weights)
+            .map(|(word, weight)| match words.contains(word) {
+                true => weight * factor,
+                false => *weight,
             })
-            .max()
-            .unwrap_or(&0);
-        let valid_sets: Vec<_> = self
-            .0
+            .collect();
+        Self::from_sorted(self.words.clone(), weights, self.ranks.clone())
+    }
+
+    /// Returns the weight of a word, or 0 if it isn't in the list.
+    pub fn weight(&self, word: &str) -> f64 {
+        self.id(word).map_or(0.0, |id| self.weights[id])
+    }
+
+    /// Returns a copy of the word list that ranks words by how common they are,
+    /// see `words::read_frequency_file`.
+    pub fn with_ranks(&self, ranks: &HashMap<String, usize>) -> WordList {
+        let ranks = self
+            .words
             .iter()
-            .filter_map(
-                |(size, set)| {
-                    if size >= max_index { Some(set) } else { None }
-                },
-            )
+            .map(|word| ranks.get(word).copied())
+            .collect();
+        Self::from_sorted(self.words.clone(), self.weights.clone(), ranks)
+    }
+
+    /// Returns how common the word is, where rank 1 is the most common word, or
+    /// `None` if the word has no rank.
+    pub fn rank(&self, word: &str) -> Option<usize> {
+        self.id(word).and_then(|id| self.ranks[id])
+    }
+
+    /// Returns a word list of the words ranked at least as common as `max_rank`.
+    pub fn up_to_rank(&self, max_rank: usize) -> WordList {
+        self.filter(|word| self.rank(word).is_some_and(|rank| rank <= max_rank))
+    }
+
+    /// Returns how familiar a word is likely to be to players, between 0 and 1, from
+    /// its rank. Words without a rank are as familiar as the rarest ranked word, so a
+    /// list without ranks treats every word alike.
+    pub fn familiarity(&self, word: &str) -> f64 {
+        let rank = self.rank(word).unwrap_or(self.unranked);
+        1.0 / (rank as f64).sqrt()
+    }
+
+    /// Returns a word list of the words the predicate keeps, along with their
+    /// weights and ranks.
+    fn filter(&self, keep: impl Fn(&str) -> bool) -> WordList {
+        let ids: Vec<usize> = (0..self.words.len())
+            .filter(|&id| keep(&self.words[id]))
             .collect();
+        Self::from_sorted(
+            ids.iter().map(|&id| self.words[id].clone()).collect(),
+            ids.iter().map(|&id| self.weights[id]).collect(),
+            ids.iter().map(|&id| self.ranks[id]).collect(),
+        )
+    }
 
-        let candidates: HashSet<String> = valid_sets
-            .into_iter()
-            .fold(HashSet::new(), |s, s2| s.union(s2).cloned().collect());
+    /// Returns the position of the word in the sorted list.
+    fn id(&self, word: &str) -> Option<usize> {
+        self.words.binary_search_by(|w| w.as_str().cmp(word)).ok()
+    }
 
-        candidates
+    /// Returns the words satisfying the constraints that can be spelled from the
+    /// bag's tiles. Letters required by `CharAt` constraints are already on the
+    /// board, so don't use a tile. With no constraints, this is every word the
+    /// bag can spell.
+    pub fn words_from_bag(&self, bag: &LetterBag, constraints: &[WordConstraint]) -> Vec<&str> {
+        let fixed: Vec<usize> = constraints
             .iter()
-            .filter(|word| constraints.iter().all(|c| c.satisfies(word)))
-            .cloned()
+            .filter_map(|c| match c {
+                WordConstraint::CharAt(index, _) => Some(*index),
+                _ => None,
+            })
+            .collect();
+        let max_length = bag.len() + fixed.len();
+        self.find_constrained_words(constraints)
+            .into_iter()
+            .filter(|word| word.chars().count() <= max_length)
+            .filter(|word| bag.can_build(word, &fixed))
             .collect()
     }
+
+    /// Returns the number of words of each length, in ascending order of length.
+    pub fn length_counts(&self) -> Vec<(usize, usize)> {
+        let mut counts: Vec<_> = self
+            .by_length
+            .iter()
+            .map(|(len, ids)| (*len, ids.len()))
+            .collect();
+        counts.sort();
+        counts
+    }
+
+    /// Loads a word list from a file, see `words::read_word_file` for the formats
+    /// supported.
+    pub fn from_path(
+        path: impl AsRef<Path>,
+        language: Language,
+    ) -> Result<WordList, LoadWordsError> {
+        Self::from_paths(&[path], language).map(|(word_list, _)| word_list)
+    }
+
+    /// Loads a word list merged from several files, returning it along with the
+    /// entries that were rejected during normalisation.
+    pub fn from_paths(
+        paths: &[impl AsRef<Path>],
+        language: Language,
+    ) -> Result<(WordList, Vec<RejectedWord>), LoadWordsError> {
+        let loaded = read_word_files(paths, language)?;
+        Ok((WordList::from_words(loaded.words), loaded.rejected))
+    }
+
+    pub fn is_word_valid(&self, word: &str) -> bool {
+        self.id(word).is_some()
+    }
+
+    pub fn frequencies(&self) -> HashMap<char, usize> {
+        self.words.iter().fold(HashMap::new(), |mut res, word| {
+            for letter in word.chars() {
+                *res.entry(letter).or_insert(0) += 1;
+            }
+            res
+        })
+    }
+
+    /// Returns how many words satisfy all of the constraints. A length with at most
+    /// one letter constraint, such as how many words of a length start or end with
+    /// a letter, is answered straight from the indexes.
+    pub fn count_constrained_words(&self, constraints: &[WordConstraint]) -> usize {
+        match constraints {
+            [WordConstraint::Length(length)] => self.length_index(*length).len(),
+            [
+                WordConstraint::Length(length),
+                WordConstraint::CharAt(index, letter),
+            ]
+            | [
+                WordConstraint::CharAt(index, letter),
+                WordConstraint::Length(length),
+            ] => self.letter_index(*length, *index, *letter).len(),
+            _ => self.find_constrained_words(constraints).len(),
+        }
+    }
+
+    /// Counts the words satisfying all of the constraints, and if a bag is supplied
+    /// that can be spelled from its tiles (see `words_from_bag`). Returns up to
+    /// `limit` of them spread evenly through the matches, shortest words first.
+    pub fn query(
+        &self,
+        constraints: &[WordConstraint],
+        bag: Option<&LetterBag>,
+        limit: usize,
+    ) -> QueryResult<'_> {
+        let matches = match bag {
+            Some(bag) => self.words_from_bag(bag, constraints),
+            None => self.find_constrained_words(constraints),
+        };
+        let count = matches.len();
+        let sample = if count <= limit {
+            matches
+        } else {
+            (0..limit).map(|i| matches[i * count / limit]).collect()
+        };
+        QueryResult { count, sample }
+    }
+
+    /// Returns every word satisfying all of the constraints, in sorted order
+    /// within each length.
+    pub fn find_constrained_words(&self, constraints: &[WordConstraint]) -> Vec<&str> {
+        let length = constraints.iter().find_map(|c| match c {
+            WordConstraint::Length(length) => Some(*length),
+            _ => None,
+        });
+        let mut lengths: Vec<usize> = match length {
+            Some(length) => vec![length],
+            None => self.by_length.keys().copied().collect(),
+        };
+        lengths.sort();
+
+        let mut result = Vec::new();
+        for length in lengths {
+            // Start from the smallest index that applies, and check the rest of
+            // the constraints on those words.
+            let smallest = constraints
+                .iter()
+                .filter_map(|c| match c {
+                    WordConstraint::CharAt(index, letter) => {
+                        Some(self.letter_index(length, *index, *letter))
+                    }
+                    _ => None,
+                })
+                .min_by_key(|ids| ids.len())
+                .unwrap_or_else(|| self.length_index(length));
+
+            result.extend(
+                smallest
+                    .iter()
+                    .map(|&id| self.words[id].as_str())
+                    .filter(|word| constraints.iter().all(|c| c.satisfies(word))),
+            );
+        }
+        result
+    }
+
+    fn length_index(&self, length: usize) -> &[usize] {
+        self.by_length
+            .get(&length)
+            .map_or(&[], |ids| ids.as_slice())
+    }
+
+    fn letter_index(&self, length: usize, index: usize, letter: char) -> &[usize] {
+        self.by_letter
+            .get(&(length, index, letter))
+            .map_or(&[], |ids| ids.as_slice())
+    }
 }
 
-#[derive(Debug, Clone)]
+/// The words matching a query, see `WordList::query`.
+#[derive(Debug)]
+pub struct QueryResult<'a> {
+    pub count: usize,
+    pub sample: Vec<&'a str>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum WordConstraint {
     Length(usize),
     CharAt(usize, char),
+    /// The letter at the index is one of the letters.
+    AnyOf(usize, Vec<char>),
+    /// The word contains the letters in a row somewhere.
+    Contains(String),
 }
 
 impl WordConstraint {
-    pub fn satisfies(&self, word: &String) -> bool {
+    pub fn satisfies(&self, word: &str) -> bool {
         match self {
-            WordConstraint::Length(size) => word.len() == *size,
-            WordConstraint::CharAt(index, letter) => {
-                word.len() > *index && word.chars().nth(*index).unwrap() == *letter
-            }
+            WordConstraint::Length(size) => word.chars().count() == *size,
+            WordConstraint::CharAt(index, letter) => word.chars().nth(*index) == Some(*letter),
+            WordConstraint::AnyOf(index, letters) => word
+                .chars()
+                .nth(*index)
+                .is_some_and(|letter| letters.contains(&letter)),
+            WordConstraint::Contains(letters) => word.contains(letters.as_str()),
         }
     }
 }
 
+/// The algorithm used to fill a solution's segments with words.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum SolverStrategy {
+    /// Choose random words, restarting on a dead end.
+    Random,
+    /// Search every combination of words, undoing earlier choices on a dead end.
+    Backtracking,
+    /// Treat the segments as a constraint satisfaction problem, propagating shared
+    /// letters in both directions and solving the most constrained segment first.
+    Csp,
+}
+
+/// How the random and backtracking solvers weigh candidate words against each other.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum WordWeighting {
+    /// Every candidate is equally likely.
+    Uniform,
+    /// Candidates are weighted by how many words could follow them in the next
+    /// segment, so words leaving a permissive letter (like S, D or T) for the next
+    /// word are preferred over those leaving an awkward one (like I).
+    Permissive,
+    /// Candidates are weighted by how common they are, so everyday words are
+    /// preferred over rare ones. Needs a word frequency list.
+    Familiar,
+}
+
+/// Options for solving a level.
+#[derive(Debug, Clone)]
+pub struct SolverConfig {
+    pub strategy: SolverStrategy,
+    pub weighting: WordWeighting,
+    /// How many times the random solver may restart before giving up.
+    pub retries: usize,
+    /// How many words the backtracking and csp solvers may try placing before
+    /// giving up.
+    pub node_budget: usize,
+}
+
+/// The result of searching for a solution.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SolveOutcome {
+    /// Every segment has a word.
+    Solved,
+    /// Every combination of words was ruled out, so no solution exists along
+    /// these segments.
+    Unsolvable,
+    /// The search gave up before it could find a solution or rule one out.
+    GaveUp,
+}
+
 pub struct SolutionWord {
     pub start: Position,
     pub end: Position,
@@ -129,9 +410,26 @@ impl SolutionWord {
     }
 }
 
+/// Returns the positions covered by a straight segment, from its start to its end.
+pub fn segment_positions((start, end): (Position, Position)) -> Vec<Position> {
+    let step = Position::new(
+        (end.row - start.row).signum(),
+        (end.col - start.col).signum(),
+    );
+    let mut result = vec![start];
+    let mut position = start;
+    while position != end {
+        position = position + step;
+        result.push(position);
+    }
+    result
+}
+
 pub struct Solution {
     words: Vec<SolutionWord>,
     segments: Vec<(Position, Position)>,
+    /// Letters already on the grid, which any word covering them must use.
+    letters: HashMap<Position, char>,
 }
 
 impl Solution {
@@ -139,13 +437,44 @@ impl Solution {
         Self {
             words: Vec::new(),
             segments,
+            letters: HashMap::new(),
         }
     }
 
+    /// Requires the words to use the letters already placed on the grid.
+    pub fn with_letters(mut self, letters: HashMap<Position, char>) -> Self {
+        self.letters = letters;
+        self
+    }
+
+    /// Returns the constraints the grid's letters place on a segment's word.
+    fn letter_constraints(&self, segment: (Position, Position)) -> Vec<WordConstraint> {
+        segment_positions(segment)
+            .into_iter()
+            .enumerate()
+            .filter_map(|(index, position)| {
+                let letter = self.letters.get(&position)?;
+                Some(WordConstraint::CharAt(index, *letter))
+            })
+            .collect()
+    }
+
     pub fn all_words(&self) -> Vec<&String> {
         self.words.iter().map(|w| &w.word).collect()
     }
 
+    /// Returns the letter each square covered by the words so far is given.
+    pub fn placed_letters(&self) -> HashMap<Position, char> {
+        self.words
+            .iter()
+            .flat_map(|word| {
+                segment_positions((word.start, word.end))
+                    .into_iter()
+                    .zip(word.word.chars())
+            })
+            .collect()
+    }
+
     pub fn last_word(&self) -> Option<&SolutionWord> {
         self.words.last()
     }
@@ -162,22 +491,27 @@ impl Solution {
         }
     }
 
-    pub fn add_word(&mut self, word: &String) {
+    pub fn add_word(&mut self, word: &str) {
         if let Some((start, end)) = self.next_segment() {
             self.words.push(SolutionWord {
                 start,
                 end,
-                word: word.clone(),
+                word: word.to_string(),
             })
         }
     }
 
+    pub fn remove_last_word(&mut self) -> Option<SolutionWord> {
+        self.words.pop()
+    }
+
     pub fn next_constraints(&self) -> Vec<WordConstraint> {
         let Some((next_start, next_stop)) = self.next_segment() else {
             return Vec::new();
         };
         let next_length = next_start.manhattan_distance(next_stop) + 1;
         let mut constraints = vec![WordConstraint::Length(next_length)];
+        constraints.extend(self.letter_constraints((next_start, next_stop)));
         let Some(word) = self.last_word() else {
             return constraints;
         };
@@ -199,14 +533,172 @@ impl Solution {
         constraints
     }
 
-    pub fn attempt_solve(&mut self, word_list: &WordList, max_attempts: usize) -> Option<()> {
+    /// Solves the level with the configured strategy.
+    pub fn solve<R: Rng + ?Sized>(
+        &mut self,
+        word_list: &WordList,
+        config: &SolverConfig,
+        rng: &mut R,
+    ) -> SolveOutcome {
+        match config.strategy {
+            SolverStrategy::Random => {
+                match self.attempt_solve(word_list, config.retries, config.weighting, rng) {
+                    Some(()) => SolveOutcome::Solved,
+                    None => SolveOutcome::GaveUp,
+                }
+            }
+            SolverStrategy::Backtracking => {
+                self.backtrack_solve(word_list, config.node_budget, config.weighting, rng)
+            }
+            SolverStrategy::Csp => self.csp_solve(word_list, config.node_budget, rng),
+        }
+    }
+
+    /// Solves every segment at once with `csp::solve_segments`, replacing any words
+    /// already placed.
+    pub fn csp_solve<R: Rng + ?Sized>(
+        &mut self,
+        word_list: &WordList,
+        node_budget: usize,
+        rng: &mut R,
+    ) -> SolveOutcome {
+        self.words.clear();
+        let constraints = self
+            .segments
+            .iter()
+            .map(|&(start, end)| {
+                let mut constraints =
+                    vec![WordConstraint::Length(start.manhattan_distance(end) + 1)];
+                constraints.extend(self.letter_constraints((start, end)));
+                constraints
+            })
+            .collect();
+        match csp::solve_segments(&self.segments, constraints, word_list, node_budget, rng) {
+            Ok(words) => {
+                for word in words {
+                    self.add_word(word);
+                }
+                SolveOutcome::Solved
+            }
+            Err(outcome) => outcome,
+        }
+    }
+
+    /// Performs a depth first search over the words of each segment, in a random
+    /// order, backing up to the previous segment when no word fits.
+    ///
+    /// Gives up after placing `node_budget` words. If every combination is
+    /// exhausted before then, the segments have no solution.
+    pub fn backtrack_solve<R: Rng + ?Sized>(
+        &mut self,
+        word_list: &WordList,
+        node_budget: usize,
+        weighting: WordWeighting,
+        rng: &mut R,
+    ) -> SolveOutcome {
+        let mut nodes = 0;
+        let mut dead_ends = HashSet::new();
+        self.backtrack(
+            word_list,
+            &mut nodes,
+            node_budget,
+            weighting,
+            &mut dead_ends,
+            rng,
+        )
+    }
+
+    fn backtrack<R: Rng + ?Sized>(
+        &mut self,
+        word_list: &WordList,
+        nodes: &mut usize,
+        node_budget: usize,
+        weighting: WordWeighting,
+        dead_ends: &mut HashSet<(usize, Vec<WordConstraint>)>,
+        rng: &mut R,
+    ) -> SolveOutcome {
+        if self.is_complete() {
+            return SolveOutcome::Solved;
+        }
+
+        // The remaining segments only depend on the constraints of the next
+        // one, so a set of constraints that failed once will always fail.
+        let key = (self.words.len(), self.next_constraints());
+        if dead_ends.contains(&key) {
+            return SolveOutcome::Unsolvable;
+        }
+
+        let candidates = word_list.find_constrained_words(&key.1);
+        let weights = self.candidate_weights(&candidates, word_list, weighting);
+        for word in weighted_order(candidates, &weights, rng) {
+            if *nodes >= node_budget {
+                return SolveOutcome::GaveUp;
+            }
+            *nodes += 1;
+
+            self.add_word(word);
+            match self.backtrack(word_list, nodes, node_budget, weighting, dead_ends, rng) {
+                SolveOutcome::Unsolvable => {
+                    self.remove_last_word();
+                }
+                outcome => return outcome,
+            }
+        }
+        dead_ends.insert(key);
+        SolveOutcome::Unsolvable
+    }
+
+    /// Weighs each candidate for the next segment according to the weighting, and
+    /// the weight of the word in the list.
+    fn candidate_weights(
+        &mut self,
+        candidates: &[&str],
+        word_list: &WordList,
+        weighting: WordWeighting,
+    ) -> Vec<f64> {
+        match weighting {
+            WordWeighting::Uniform => candidates
+                .iter()
+                .map(|word| word_list.weight(word))
+                .collect(),
+            WordWeighting::Familiar => candidates
+                .iter()
+                .map(|word| word_list.familiarity(word) * word_list.weight(word))
+                .collect(),
+            WordWeighting::Permissive => candidates
+                .iter()
+                .map(|word| {
+                    self.add_word(word);
+                    let constraints = self.next_constraints();
+                    let continuations = if constraints.is_empty() {
+                        1
+                    } else {
+                        word_list.count_constrained_words(&constraints)
+                    };
+                    self.remove_last_word();
+                    continuations as f64 * word_list.weight(word)
+                })
+                .collect(),
+        }
+    }
+
+    pub fn attempt_solve<R: Rng + ?Sized>(
+        &mut self,
+        word_list: &WordList,
+        max_attempts: usize,
+        weighting: WordWeighting,
+        rng: &mut R,
+    ) -> Option<()> {
         let mut attempts = 0;
         'solving: while attempts < max_attempts {
+            // Start each attempt afresh, rather than from the dead end.
+            self.words.clear();
             while !self.is_complete() {
                 let constraints = self.next_constraints();
-                let candidates = word_list.find_constrained_words(constraints.clone());
+                let candidates = word_list.find_constrained_words(&constraints);
+                let weights = self.candidate_weights(&candidates, word_list, weighting);
                 // Choose a random solution from candidates
-                match candidates.iter().choose(&mut rand::rng()) {
+                match weighted_order(candidates, &weights, rng).first() {
                     Some(word) => {
                         self.add_word(word);
                     }
@@ -223,3 +715,235 @@ impl Solution {
         None
     }
 }
+
+/// Returns a random ordering of the candidates where each one is more likely to come
+/// first the higher its weight, leaving out those with no weight.
+pub(crate) fn weighted_order<'a, R: Rng + ?Sized>(
+    candidates: Vec<&'a str>,
+    weights: &[f64],
+    rng: &mut R,
+) -> Vec<&'a str> {
+    // Weighted sampling without replacement (Efraimidis and Spirakis), which
+    // sorts by a random key of u^(1 / weight).
+    let mut keyed: Vec<(f64, &str)> = candidates
+        .into_iter()
+        .zip(weights)
+        .filter(|(_, weight)| **weight > 0.0)
+        .map(|(word, weight)| (rng.random::<f64>().powf(1.0 / weight), word))
+        .collect();
+    keyed.sort_by(|a, b| b.0.total_cmp(&a.0));
+    keyed.into_iter().map(|(_, word)| word).collect()
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::level_rng;
+
+    pub(crate) fn word_list(words: &[&str]) -> WordList {
+        WordList::from_words(words.iter().map(|word| word.to_string()).collect())
+    }
+
+    fn backtracking(node_budget: usize) -> SolverConfig {
+        SolverConfig {
+            strategy: SolverStrategy::Backtracking,
+            weighting: WordWeighting::Uniform,
+            retries: 0,
+            node_budget,
+        }
+    }
+
+    /// Along the top row, then down the right hand side, then back along the
+    /// bottom row.
+    fn solution() -> Solution {
+        Solution::new(vec![
+            (Position::new(0, 0), Position::new(0, 2)),
+            (Position::new(0, 2), Position::new(2, 2)),
+            (Position::new(2, 2), Position::new(2, 0)),
+        ])
+    }
+
+    #[test]
+    fn indexed_lookups_match_a_plain_filter() {
+        let words = word_list(&[
+            "a", "at", "cat", "cot", "act", "tact", "coat", "taco", "cast", "scat", "tacos",
+        ]);
+        let queries = vec![
+            vec![WordConstraint::Length(3)],
+            vec![WordConstraint::Length(4), WordConstraint::CharAt(0, 't')],
+            vec![WordConstraint::CharAt(3, 't'), WordConstraint::Length(4)],
+            vec![
+                WordConstraint::Length(4),
+                WordConstraint::CharAt(1, 'a'),
+                WordConstraint::CharAt(3, 't'),
+            ],
+            // Past the end of every word of the length.
+            vec![WordConstraint::Length(3), WordConstraint::CharAt(5, 's')],
+            vec![WordConstraint::Length(7)],
+            // No length, so every length is searched.
+            vec![WordConstraint::CharAt(1, 'a')],
+            vec![
+                WordConstraint::CharAt(0, 'c'),
+                WordConstraint::CharAt(2, 't'),
+            ],
+            vec![WordConstraint::CharAt(4, 's')],
+            vec![],
+        ];
+
+        for constraints in queries {
+            let mut expected: Vec<&str> = words
+                .words()
+                .filter(|word| constraints.iter().all(|c| c.satisfies(word)))
+                .collect();
+            expected.sort_by_key(|word| (word.chars().count(), *word));
+            assert_eq!(
+                words.find_constrained_words(&constraints),
+                expected,
+                "{:?}",
+                constraints
+            );
+            assert_eq!(
+                words.count_constrained_words(&constraints),
+                expected.len(),
+                "{:?}",
+                constraints
+            );
+        }
+    }
+
+    #[test]
+    fn lengths_count_characters_not_bytes() {
+        let words = word_list(&["año", "pan", "años"]);
+        assert_eq!("año".len(), 4);
+        assert_eq!(
+            words.find_constrained_words(&[WordConstraint::Length(3)]),
+            vec!["año", "pan"]
+        );
+        assert_eq!(
+            words.count_constrained_words(&[WordConstraint::Length(4)]),
+            1
+        );
+        assert_eq!(words.length_counts(), vec![(3, 2), (4, 1)]);
+        assert_eq!(
+            words.find_constrained_words(&[
+                WordConstraint::Length(3),
+                WordConstraint::CharAt(1, 'ñ')
+            ]),
+            vec!["año"]
+        );
+    }
+
+    #[test]
+    fn words_from_bag_fit_the_tiles() {
+        let words = word_list(&["at", "tat", "tact", "cat", "scat", "act", "tacts"]);
+        let bag = LetterBag::from_words(["tac"]);
+        assert_eq!(words.words_from_bag(&bag, &[]), vec!["at", "act", "cat"]);
+
+        // The letters on the board don't use a tile, so allow one letter longer
+        // words, but no longer than that.
+        let constraints = [WordConstraint::CharAt(0, 't')];
+        assert_eq!(
+            words.words_from_bag(&bag, &constraints),
+            vec!["tat", "tact"]
+        );
+        let constraints = [WordConstraint::CharAt(0, 's'), WordConstraint::Length(4)];
+        assert_eq!(words.words_from_bag(&bag, &constraints), vec!["scat"]);
+        // "tacts" is longer than the tiles and the letter on the board.
+        let constraints = [WordConstraint::CharAt(4, 's')];
+        assert!(words.words_from_bag(&bag, &constraints).is_empty());
+    }
+
+    #[test]
+    fn ranks_limit_and_weight_words() {
+        let ranks = HashMap::from([("the".to_string(), 1), ("cat".to_string(), 4)]);
+        let words = word_list(&["the", "cat", "gnu"]).with_ranks(&ranks);
+        assert_eq!(words.rank("cat"), Some(4));
+        assert_eq!(words.rank("gnu"), None);
+        assert_eq!(
+            words.up_to_rank(4).words().collect::<Vec<_>>(),
+            vec!["cat", "the"]
+        );
+        assert_eq!(words.up_to_rank(3).words().collect::<Vec<_>>(), vec!["the"]);
+
+        assert_eq!(words.familiarity("the"), 1.0);
+        assert_eq!(words.familiarity("cat"), 0.5);
+        // Unranked words are rarer than every ranked word.
+        assert!(words.familiarity("gnu") < words.familiarity("cat"));
+        // Without ranks every word is as familiar as the rest.
+        let unranked = word_list(&["the", "gnu"]);
+        assert_eq!(unranked.familiarity("the"), unranked.familiarity("gnu"));
+    }
+
+    #[test]
+    fn permissive_weighting_prefers_words_with_more_continuations() {
+        // Three words start with "t", one with "b" and none with "w".
+        let words = word_list(&["cat", "cab", "cow", "tin", "top", "tea", "bat"]);
+        let mut solution = solution();
+        let candidates = vec!["cat", "cab", "cow"];
+        let weights = solution.candidate_weights(&candidates, &words, WordWeighting::Permissive);
+        assert_eq!(weights, vec![3.0, 1.0, 0.0]);
+        assert!(solution.all_words().is_empty());
+
+        let mut rng = level_rng(0);
+        let mut cat_first = 0;
+        for _ in 0..100 {
+            let order = weighted_order(candidates.clone(), &weights, &mut rng);
+            assert!(!order.contains(&"cow"));
+            if order[0] == "cat" {
+                cat_first += 1;
+            }
+        }
+        assert!(cat_first > 60, "{}", cat_first);
+    }
+
+    #[test]
+    fn random_solver_retries_after_a_dead_end() {
+        // Starting with "cat" or "tin" leads to a word ending in "n", which no
+        // word follows.
+        let words = word_list(&["cat", "tin", "dog", "gum", "mud"]);
+        let config = SolverConfig {
+            strategy: SolverStrategy::Random,
+            weighting: WordWeighting::Uniform,
+            retries: 50,
+            node_budget: 0,
+        };
+        for seed in 0..20 {
+            let mut solution = solution();
+            let outcome = solution.solve(&words, &config, &mut level_rng(seed));
+            assert_eq!(outcome, SolveOutcome::Solved);
+            assert_eq!(solution.all_words().len(), 3);
+        }
+    }
+
+    #[test]
+    fn backtracking_finds_a_chain() {
+        let words = word_list(&["cat", "tin", "ten", "nap", "nod", "dog"]);
+        let mut solution = solution();
+        let outcome = solution.solve(&words, &backtracking(1000), &mut level_rng(0));
+        assert_eq!(outcome, SolveOutcome::Solved);
+        let placed: Vec<&str> = solution
+            .all_words()
+            .into_iter()
+            .map(|w| w.as_str())
+            .collect();
+        assert_eq!(placed[0].chars().last(), placed[1].chars().next());
+        assert_eq!(placed[1].chars().last(), placed[2].chars().next());
+    }
+
+    #[test]
+    fn backtracking_rules_out_chains_that_cannot_link() {
+        // "tin" and "ten" follow "cat", but nothing follows them.
+        let words = word_list(&["cat", "tin", "ten", "dog"]);
+        for seed in 0..5 {
+            let outcome = solution().solve(&words, &backtracking(1000), &mut level_rng(seed));
+            assert_eq!(outcome, SolveOutcome::Unsolvable);
+        }
+    }
+
+    #[test]
+    fn backtracking_gives_up_without_a_budget() {
+        let words = word_list(&["cat", "tin", "ten", "nap", "nod", "dog"]);
+        let outcome = solution().solve(&words, &backtracking(0), &mut level_rng(0));
+        assert_eq!(outcome, SolveOutcome::GaveUp);
+    }
+}
@@ -0,0 +1,256 @@
+use std::{collections::HashSet, fmt, fs, io, path::Path};
+
+use crate::{
+    game::{Entity, Grid},
+    position::Position,
+};
+
+/// A hand drawn board, with an optional start and goal.
+#[derive(Debug, Clone)]
+pub struct Template {
+    pub grid: Grid,
+    pub start: Option<Position>,
+    pub goal: Option<Position>,
+}
+
+/// The ways reading a template can fail. Lines and columns are 1-based.
+#[derive(Debug)]
+pub enum TemplateError {
+    FileError(io::Error),
+    Empty,
+    UnknownCharacter {
+        line: usize,
+        col: usize,
+        found: char,
+    },
+    DuplicateMarker {
+        line: usize,
+        col: usize,
+        marker: char,
+    },
+    /// The start and goal are in rooms that aren't joined.
+    Disconnected,
+    /// The room levels would be placed in has fewer than two squares.
+    RoomTooSmall,
+}
+
+impl fmt::Display for TemplateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TemplateError::FileError(error) => write!(f, "could not read template: {}", error),
+            TemplateError::Empty => write!(f, "the template has no squares"),
+            TemplateError::UnknownCharacter { line, col, found } => write!(
+                f,
+                "{}:{}: {:?} is not '#', '.', ' ', '~', 'S', 'G' or a lowercase letter",
+                line, col, found
+            ),
+            TemplateError::DuplicateMarker { line, col, marker } => {
+                write!(f, "{}:{}: the board already has an {:?}", line, col, marker)
+            }
+            TemplateError::Disconnected => {
+                write!(f, "the start and goal are in different rooms")
+            }
+            TemplateError::RoomTooSmall => {
+                write!(f, "the room for levels needs at least two squares")
+            }
+        }
+    }
+}
+
+impl std::error::Error for TemplateError {}
+
+impl Template {
+    /// Returns the free squares of the room levels are placed in: the room holding
+    /// the start or goal, or the largest room if neither is marked.
+    pub fn room(&self) -> HashSet<Position> {
+        match self.start.or(self.goal) {
+            Some(position) => self.grid.explore_section(position),
+            None => self
+                .grid
+                .find_connected_sections()
+                .into_iter()
+                .max_by_key(|section| section.len())
+                .unwrap_or_default(),
+        }
+    }
+}
+
+/// Reads a template from a file, see `parse_template`.
+pub fn load_template(path: impl AsRef<Path>) -> Result<Template, TemplateError> {
+    let text = fs::read_to_string(path).map_err(TemplateError::FileError)?;
+    parse_template(&text)
+}
+
+/// Parses a board drawn in the format `Level::visualise` prints, one row per line:
+/// - `#` is a wall.
+/// - `.` or a space is a free square.
+/// - `~` is void, outside the board.
+/// - `S` and `G` mark the start and goal, and can each appear at most once.
+/// - A lowercase letter is a letter placed on the board, which every solution
+///   must use.
+///
+/// The start and goal must be in the same room, and the room must have at least
+/// two squares. Rows shorter than the longest are padded with free squares, as
+/// editors often strip trailing spaces. If there is a line of `=`, the board is
+/// read from the first such line to the next, ignoring anything around it, so the
+/// output of `show` can be read back. Only the first level shown is read.
+pub fn parse_template(text: &str) -> Result<Template, TemplateError> {
+    let is_bar = |line: &str| !line.is_empty() && line.chars().all(|c| c == '=');
+    let mut lines: Vec<(usize, &str)> = text
+        .lines()
+        .enumerate()
+        .map(|(i, line)| (i + 1, line))
+        .collect();
+    match lines.iter().position(|(_, line)| is_bar(line)) {
+        // Rows of spaces between the bars are free squares, so are kept.
+        Some(bar) => {
+            lines = lines
+                .into_iter()
+                .skip(bar + 1)
+                .take_while(|(_, line)| !is_bar(line))
+                .collect();
+        }
+        None => {
+            let blank = lines
+                .iter()
+                .take_while(|(_, line)| line.trim().is_empty())
+                .count();
+            lines.drain(..blank);
+            while lines.last().is_some_and(|(_, line)| line.trim().is_empty()) {
+                lines.pop();
+            }
+        }
+    }
+
+    let rows = lines.len();
+    let cols = lines
+        .iter()
+        .map(|(_, line)| line.chars().count())
+        .max()
+        .unwrap_or(0);
+    if rows == 0 || cols == 0 {
+        return Err(TemplateError::Empty);
+    }
+
+    let mut template = Template {
+        grid: Grid::new(rows, cols),
+        start: None,
+        goal: None,
+    };
+    for (row, (line, text)) in lines.into_iter().enumerate() {
+        for (col, c) in text.chars().enumerate() {
+            let position = Position::new(row as isize, col as isize);
+            let marker = match c {
+                '#' => {
+                    template.grid.entities.insert(position, Entity::Wall);
+                    continue;
+                }
+                '.' | ' ' => continue,
+                '~' => {
+                    template.grid.entities.insert(position, Entity::Void);
+                    continue;
+                }
+                'S' => &mut template.start,
+                'G' => &mut template.goal,
+                letter if letter.is_lowercase() => {
+                    template
+                        .grid
+                        .entities
+                        .insert(position, Entity::Letter(letter));
+                    continue;
+                }
+                found => {
+                    return Err(TemplateError::UnknownCharacter {
+                        line,
+                        col: col + 1,
+                        found,
+                    });
+                }
+            };
+            if marker.is_some() {
+                return Err(TemplateError::DuplicateMarker {
+                    line,
+                    col: col + 1,
+                    marker: c,
+                });
+            }
+            *marker = Some(position);
+        }
+    }
+
+    let room = template.room();
+    if let (Some(_), Some(goal)) = (template.start, template.goal)
+        && !room.contains(&goal)
+    {
+        return Err(TemplateError::Disconnected);
+    }
+    if room.len() < 2 {
+        return Err(TemplateError::RoomTooSmall);
+    }
+    Ok(template)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_the_board() {
+        let template = parse_template("S.#\n~a\n..G\n").unwrap();
+        let grid = &template.grid;
+        assert_eq!((grid.rows, grid.cols), (3, 3));
+        assert_eq!(template.start, Some(Position::new(0, 0)));
+        assert_eq!(template.goal, Some(Position::new(2, 2)));
+        assert!(grid.is_wall(Position::new(0, 2)));
+        assert!(grid.is_void(Position::new(1, 0)));
+        assert_eq!(
+            grid.entities.get(&Position::new(1, 1)),
+            Some(&Entity::Letter('a'))
+        );
+        // The short row is padded with a free square.
+        assert_eq!(grid.entities.get(&Position::new(1, 2)), None);
+    }
+
+    #[test]
+    fn reads_back_visualised_levels() {
+        let shown = "Level 2025-01-01 (seed 42)\n===\nS #\n  G\n   \n===\nSolution: [\"sag\"]\n\
+            Path: 0_0 -> 1_0 -> 1_2\nLevel 2025-01-02 (seed 43)\n===\nS..\n..G\n===\n";
+        let template = parse_template(shown).unwrap();
+        // The last row is all free squares.
+        assert_eq!((template.grid.rows, template.grid.cols), (3, 3));
+        assert_eq!(template.goal, Some(Position::new(1, 2)));
+    }
+
+    #[test]
+    fn rejects_bad_boards() {
+        assert!(matches!(parse_template("\n\n"), Err(TemplateError::Empty)));
+        assert!(matches!(
+            parse_template("..\n.X"),
+            Err(TemplateError::UnknownCharacter {
+                line: 2,
+                col: 2,
+                found: 'X'
+            })
+        ));
+        assert!(matches!(
+            parse_template("S.S"),
+            Err(TemplateError::DuplicateMarker {
+                line: 1,
+                col: 3,
+                marker: 'S'
+            })
+        ));
+    }
+
+    #[test]
+    fn rejects_unusable_rooms() {
+        assert!(matches!(
+            parse_template("S..#...\n...#...\n...#..G"),
+            Err(TemplateError::Disconnected)
+        ));
+        assert!(matches!(
+            parse_template("S#\n##"),
+            Err(TemplateError::RoomTooSmall)
+        ));
+    }
+}
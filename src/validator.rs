@@ -0,0 +1,412 @@
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{HashMap, HashSet},
+    fmt,
+};
+
+use crate::{
+    game::{Entity, Level},
+    language::Language,
+    position::{Direction, Position},
+    solver::WordList,
+};
+
+/// The direction a word is read in. Words can only be read from left to right
+/// or from top to bottom.
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Orientation {
+    Across,
+    Down,
+}
+
+impl Orientation {
+    fn direction(self) -> Direction {
+        match self {
+            Orientation::Across => Direction::Right,
+            Orientation::Down => Direction::Down,
+        }
+    }
+}
+
+/// A word a player has placed on the grid, starting from its first letter.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct PlacedWord {
+    pub word: String,
+    pub start: Position,
+    pub orientation: Orientation,
+}
+
+impl PlacedWord {
+    /// Returns each letter of the word along with the position it occupies.
+    pub fn letters(&self) -> Vec<(Position, char)> {
+        let mut position = self.start;
+        let mut result = Vec::new();
+        for letter in self.word.chars() {
+            result.push((position, letter));
+            position = position.step_in_direction(self.orientation.direction());
+        }
+        result
+    }
+}
+
+/// The reasons a player's solution can break the rules of wordcrossing.
+#[derive(PartialEq, Eq, Debug)]
+pub enum ValidationError {
+    NoWords,
+    UnknownWord(String),
+    OutOfBounds(String),
+    OnWall(String, Position),
+    LetterMismatch(Position, char, char),
+    Overlapping(String, String),
+    Disconnected(String),
+    StartNotReached,
+    GoalNotReached,
+    NotEnoughLetters(char),
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ValidationError::NoWords => write!(f, "no words were placed"),
+            ValidationError::UnknownWord(word) => write!(f, "{:?} is not a valid word", word),
+            ValidationError::OutOfBounds(word) => write!(f, "{:?} leaves the grid", word),
+            ValidationError::OnWall(word, p) => {
+                write!(f, "{:?} is placed on a wall at {}", word, p.to_key())
+            }
+            ValidationError::LetterMismatch(p, expected, found) => write!(
+                f,
+                "{} needs to be {:?} but a word places {:?} there",
+                p.to_key(),
+                expected,
+                found
+            ),
+            ValidationError::Overlapping(first, second) => {
+                write!(
+                    f,
+                    "{:?} and {:?} overlap in the same direction",
+                    first, second
+                )
+            }
+            ValidationError::Disconnected(word) => {
+                write!(f, "{:?} is not connected to the start", word)
+            }
+            ValidationError::StartNotReached => write!(f, "no word covers the start"),
+            ValidationError::GoalNotReached => write!(f, "the words don't reach the goal"),
+            ValidationError::NotEnoughLetters(letter) => {
+                write!(f, "not enough {:?} tiles in the level's letters", letter)
+            }
+        }
+    }
+}
+
+/// Summary of a valid solution, used for scoring.
+#[derive(PartialEq, Eq, Debug)]
+pub struct ValidSolution {
+    pub word_count: usize,
+    pub letters_used: usize,
+}
+
+/// Checks a player's placed words against the rules of wordcrossing:
+/// - Every word is in the word list, and lies on the grid away from walls.
+/// - Words that share a square agree on its letter, including letters already
+///   on the grid.
+/// - Words in the same direction share at most one square, where one ends and
+///   the next begins.
+/// - The words are connected to each other, covering both the start and goal.
+/// - The letters placed can be taken from the letters of the level.
+///
+/// Words are normalised for the language first, the same way the word list was
+/// when it was loaded, so `BIN` or an accented Spanish word is matched.
+pub fn validate_solution(
+    level: &Level,
+    words: &[PlacedWord],
+    word_list: &WordList,
+    language: Language,
+) -> Result<ValidSolution, ValidationError> {
+    if words.is_empty() {
+        return Err(ValidationError::NoWords);
+    }
+    let words: Vec<PlacedWord> = words
+        .iter()
+        .map(|placed| match language.normalise(&placed.word) {
+            Some(word) => Ok(PlacedWord {
+                word,
+                ..placed.clone()
+            }),
+            None => Err(ValidationError::UnknownWord(placed.word.clone())),
+        })
+        .collect::<Result<_, _>>()?;
+
+    let mut board: HashMap<Position, char> = HashMap::new();
+    for placed in words.iter() {
+        if !word_list.is_word_valid(&placed.word) {
+            return Err(ValidationError::UnknownWord(placed.word.clone()));
+        }
+        for (position, letter) in placed.letters() {
+            if !position.is_within_bounds(level.grid.rows as isize, level.grid.cols as isize) {
+                return Err(ValidationError::OutOfBounds(placed.word.clone()));
+            }
+            let expected = match level.grid.entities.get(&position) {
+                Some(Entity::Wall) => {
+                    return Err(ValidationError::OnWall(placed.word.clone(), position));
+                }
+                Some(Entity::Void) => {
+                    return Err(ValidationError::OutOfBounds(placed.word.clone()));
+                }
+                Some(Entity::Letter(fixed)) => Some(*fixed),
+                _ => board.get(&position).copied(),
+            };
+            if let Some(expected) = expected
+                && expected != letter
+            {
+                return Err(ValidationError::LetterMismatch(position, expected, letter));
+            }
+            board.insert(position, letter);
+        }
+    }
+
+    let cells: Vec<HashSet<Position>> = words
+        .iter()
+        .map(|w| w.letters().into_iter().map(|(p, _)| p).collect())
+        .collect();
+    for i in 0..words.len() {
+        for j in i + 1..words.len() {
+            if words[i].orientation == words[j].orientation
+                && cells[i].intersection(&cells[j]).count() > 1
+            {
+                return Err(ValidationError::Overlapping(
+                    words[i].word.clone(),
+                    words[j].word.clone(),
+                ));
+            }
+        }
+    }
+
+    // Words are connected when they share a square. Walk outwards from the
+    // words covering the start.
+    let mut queue: Vec<usize> = (0..words.len())
+        .filter(|&i| cells[i].contains(&level.start))
+        .collect();
+    if queue.is_empty() {
+        return Err(ValidationError::StartNotReached);
+    }
+    let mut connected: HashSet<usize> = queue.iter().copied().collect();
+    while let Some(i) = queue.pop() {
+        for j in 0..words.len() {
+            if !connected.contains(&j) && !cells[i].is_disjoint(&cells[j]) {
+                connected.insert(j);
+                queue.push(j);
+            }
+        }
+    }
+    if let Some(i) = (0..words.len()).find(|i| !connected.contains(i)) {
+        return Err(ValidationError::Disconnected(words[i].word.clone()));
+    }
+    if !board.contains_key(&level.goal) {
+        return Err(ValidationError::GoalNotReached);
+    }
+
+    // Letters already on the grid don't need to come from the level's letters.
+    let mut available = level.letters();
+    let mut placed: Vec<_> = board
+        .iter()
+        .filter(|(p, _)| !matches!(level.grid.entities.get(p), Some(Entity::Letter(_))))
+        .collect();
+    placed.sort();
+    for (_, letter) in placed.iter() {
+        if !available.take(**letter) {
+            return Err(ValidationError::NotEnoughLetters(**letter));
+        }
+    }
+
+    Ok(ValidSolution {
+        word_count: words.len(),
+        letters_used: placed.len(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{game::Grid, solver::tests::word_list};
+
+    fn level() -> Level {
+        let mut grid = Grid::new(3, 3);
+        grid.entities.insert(Position::new(2, 0), Entity::Wall);
+        Level {
+            start: Position::new(0, 0),
+            goal: Position::new(2, 2),
+            words: vec!["cat".to_string(), "tin".to_string()],
+            grid,
+            path: Vec::new(),
+            seed: 0,
+            tiles: None,
+        }
+    }
+
+    fn placed(word: &str, row: isize, col: isize, orientation: Orientation) -> PlacedWord {
+        PlacedWord {
+            word: word.to_string(),
+            start: Position::new(row, col),
+            orientation,
+        }
+    }
+
+    fn validate(level: &Level, words: &[PlacedWord]) -> Result<ValidSolution, ValidationError> {
+        let dictionary = word_list(&["cat", "cot", "tin", "at", "in", "can"]);
+        validate_solution(level, words, &dictionary, Language::English)
+    }
+
+    #[test]
+    fn accepts_the_solution() {
+        let words = [
+            placed("cat", 0, 0, Orientation::Across),
+            placed("tin", 0, 2, Orientation::Down),
+        ];
+        assert_eq!(
+            validate(&level(), &words),
+            Ok(ValidSolution {
+                word_count: 2,
+                letters_used: 5
+            })
+        );
+    }
+
+    #[test]
+    fn normalises_words_before_checking_them() {
+        let words = [
+            placed("CAT", 0, 0, Orientation::Across),
+            placed(" Tin", 0, 2, Orientation::Down),
+        ];
+        assert!(validate(&level(), &words).is_ok());
+    }
+
+    #[test]
+    fn rejects_unknown_words() {
+        let words = [placed("c4t", 0, 0, Orientation::Across)];
+        assert_eq!(
+            validate(&level(), &words),
+            Err(ValidationError::UnknownWord("c4t".to_string()))
+        );
+    }
+
+    #[test]
+    fn rejects_words_on_walls_or_off_the_grid() {
+        let words = [placed("can", 0, 0, Orientation::Down)];
+        assert_eq!(
+            validate(&level(), &words),
+            Err(ValidationError::OnWall(
+                "can".to_string(),
+                Position::new(2, 0)
+            ))
+        );
+        let words = [placed("cat", 0, 1, Orientation::Across)];
+        assert_eq!(
+            validate(&level(), &words),
+            Err(ValidationError::OutOfBounds("cat".to_string()))
+        );
+    }
+
+    #[test]
+    fn rejects_words_overlapping_in_the_same_direction() {
+        let words = [
+            placed("cat", 0, 0, Orientation::Across),
+            placed("tin", 0, 2, Orientation::Down),
+            placed("at", 0, 1, Orientation::Across),
+        ];
+        assert_eq!(
+            validate(&level(), &words),
+            Err(ValidationError::Overlapping(
+                "cat".to_string(),
+                "at".to_string()
+            ))
+        );
+
+        let words = [
+            placed("cat", 0, 0, Orientation::Across),
+            placed("tin", 0, 2, Orientation::Down),
+            placed("tin", 0, 2, Orientation::Down),
+        ];
+        assert!(matches!(
+            validate(&level(), &words),
+            Err(ValidationError::Overlapping(..))
+        ));
+    }
+
+    #[test]
+    fn rejects_disagreeing_letters() {
+        let words = [
+            placed("cat", 0, 0, Orientation::Across),
+            placed("in", 0, 2, Orientation::Down),
+        ];
+        assert_eq!(
+            validate(&level(), &words),
+            Err(ValidationError::LetterMismatch(
+                Position::new(0, 2),
+                't',
+                'i'
+            ))
+        );
+    }
+
+    #[test]
+    fn requires_the_start_and_goal() {
+        let words = [placed("in", 1, 1, Orientation::Across)];
+        assert_eq!(
+            validate(&level(), &words),
+            Err(ValidationError::StartNotReached)
+        );
+        let words = [placed("cat", 0, 0, Orientation::Across)];
+        assert_eq!(
+            validate(&level(), &words),
+            Err(ValidationError::GoalNotReached)
+        );
+    }
+
+    #[test]
+    fn rejects_words_without_enough_tiles() {
+        let words = [
+            placed("cot", 0, 0, Orientation::Across),
+            placed("tin", 0, 2, Orientation::Down),
+        ];
+        assert_eq!(
+            validate(&level(), &words),
+            Err(ValidationError::NotEnoughLetters('o'))
+        );
+    }
+
+    #[test]
+    fn locked_letters_are_used_without_a_tile() {
+        let mut level = level();
+        level
+            .grid
+            .entities
+            .insert(Position::new(0, 1), Entity::Letter('a'));
+        level.path = vec![
+            Position::new(0, 0),
+            Position::new(0, 2),
+            Position::new(2, 2),
+        ];
+        level.record_tiles();
+        assert_eq!(level.letters().count('a'), 0);
+        let words = [
+            placed("cat", 0, 0, Orientation::Across),
+            placed("tin", 0, 2, Orientation::Down),
+        ];
+        assert!(validate(&level, &words).is_ok());
+
+        let words = [
+            placed("cot", 0, 0, Orientation::Across),
+            placed("tin", 0, 2, Orientation::Down),
+        ];
+        assert_eq!(
+            validate(&level, &words),
+            Err(ValidationError::LetterMismatch(
+                Position::new(0, 1),
+                'a',
+                'o'
+            ))
+        );
+    }
+}
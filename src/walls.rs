@@ -0,0 +1,381 @@
+use clap::ValueEnum;
+use rand::{RngCore, prelude::*};
+
+use crate::{
+    game::{Entity, Grid},
+    position::Position,
+};
+
+/// A way of placing walls on an empty grid. The largest room left free becomes
+/// the level, see `Grid::initialise_walls`.
+pub trait WallStrategy: Sync {
+    fn place_walls(&self, grid: &mut Grid, rng: &mut dyn RngCore);
+}
+
+/// The wall strategies that can be chosen for a run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum WallStyle {
+    /// Walls scattered uniformly at random, giving jagged boards.
+    Noise,
+    /// Smooth, organic caves grown with a cellular automaton.
+    Caves,
+    /// Maze-like chambers from recursive division.
+    Maze,
+    /// Rectangular rooms joined by corridors.
+    Rooms,
+    /// Clumps of walls placed around the board.
+    Blobs,
+}
+
+impl WallStyle {
+    /// Returns the strategy with its default settings.
+    pub fn strategy(self) -> Box<dyn WallStrategy> {
+        match self {
+            WallStyle::Noise => Box::new(UniformNoise {
+                min_area: 0.15,
+                max_area: 0.5,
+            }),
+            WallStyle::Caves => Box::new(CellularCaves {
+                fill: 0.5,
+                steps: 2,
+            }),
+            WallStyle::Maze => Box::new(RecursiveDivision { min_size: 3 }),
+            WallStyle::Rooms => Box::new(RoomsAndCorridors {
+                min_rooms: 2,
+                max_rooms: 4,
+                max_room_size: 4,
+            }),
+            WallStyle::Blobs => Box::new(Blobs {
+                min_area: 0.2,
+                max_area: 0.4,
+                max_blob_size: 5,
+            }),
+        }
+    }
+}
+
+/// The symmetry of a grid's walls, like the symmetry of a published crossword.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Symmetry {
+    /// Walls are left as the strategy placed them.
+    None,
+    /// The walls look the same after turning the grid 180 degrees.
+    Rotational,
+    /// The bottom half of the grid mirrors the top half.
+    Horizontal,
+    /// The right half of the grid mirrors the left half.
+    Vertical,
+    /// The grid is mirrored both top to bottom and left to right.
+    FourWay,
+}
+
+impl Symmetry {
+    /// Returns the squares that must match the position in a grid of the given
+    /// size, including the position itself.
+    pub fn images(self, position: Position, rows: usize, cols: usize) -> Vec<Position> {
+        let flipped_row = rows as isize - 1 - position.row;
+        let flipped_col = cols as isize - 1 - position.col;
+        let mut images = vec![position];
+        match self {
+            Symmetry::None => {}
+            Symmetry::Rotational => images.push(Position::new(flipped_row, flipped_col)),
+            Symmetry::Horizontal => images.push(Position::new(flipped_row, position.col)),
+            Symmetry::Vertical => images.push(Position::new(position.row, flipped_col)),
+            Symmetry::FourWay => images.extend([
+                Position::new(flipped_row, position.col),
+                Position::new(position.row, flipped_col),
+                Position::new(flipped_row, flipped_col),
+            ]),
+        }
+        images.sort();
+        images.dedup();
+        images
+    }
+}
+
+/// Walls off a random share of the squares, between `min_area` and `max_area`.
+pub struct UniformNoise {
+    pub min_area: f32,
+    pub max_area: f32,
+}
+
+impl WallStrategy for UniformNoise {
+    fn place_walls(&self, grid: &mut Grid, rng: &mut dyn RngCore) {
+        grid.randomise_walls(self.min_area, self.max_area, rng);
+    }
+}
+
+/// Walls off each square with probability `fill`, then smooths the walls over
+/// `steps` rounds: a square becomes a wall when at least five of its eight
+/// neighbours are walls, stays a wall with at least four, and is otherwise free.
+pub struct CellularCaves {
+    pub fill: f64,
+    pub steps: usize,
+}
+
+impl WallStrategy for CellularCaves {
+    fn place_walls(&self, grid: &mut Grid, rng: &mut dyn RngCore) {
+        let positions = grid.all_positions();
+        for position in positions.iter() {
+            if rng.random_bool(self.fill) {
+                grid.set_positions(vec![*position], Entity::Wall);
+            }
+        }
+
+        for _ in 0..self.steps {
+            let mut next = grid.clone();
+            for position in positions.iter() {
+                let walls = surrounding(*position).filter(|p| grid.is_wall(*p)).count();
+                if walls >= 5 || (grid.is_wall(*position) && walls >= 4) {
+                    next.set_positions(vec![*position], Entity::Wall);
+                } else {
+                    next.clear(*position);
+                }
+            }
+            *grid = next;
+        }
+    }
+}
+
+/// Returns the eight squares around a position, including diagonals.
+fn surrounding(position: Position) -> impl Iterator<Item = Position> {
+    (-1..=1)
+        .flat_map(|row| (-1..=1).map(move |col| Position::new(row, col)))
+        .filter(|offset| *offset != Position::new(0, 0))
+        .map(move |offset| position + offset)
+}
+
+/// Splits the grid into chambers with walls that each have a single gap, then
+/// splits those chambers in turn until they are smaller than `min_size` in
+/// either direction.
+///
+/// Walls are placed an odd number of squares from the chamber's edge and gaps an
+/// even number, so every chamber starts on an even row and column and a wall can
+/// never end against the gap of the wall it splits off from.
+pub struct RecursiveDivision {
+    pub min_size: usize,
+}
+
+impl WallStrategy for RecursiveDivision {
+    fn place_walls(&self, grid: &mut Grid, rng: &mut dyn RngCore) {
+        self.divide(grid, (0, 0), (grid.rows, grid.cols), rng);
+    }
+}
+
+impl RecursiveDivision {
+    /// Divides the chamber with its top left corner at `(row, col)`, spanning
+    /// `(rows, cols)` squares.
+    fn divide(
+        &self,
+        grid: &mut Grid,
+        (row, col): (usize, usize),
+        (rows, cols): (usize, usize),
+        rng: &mut dyn RngCore,
+    ) {
+        if rows < self.min_size || cols < self.min_size {
+            return;
+        }
+
+        // Split across the longer side, so chambers stay roughly square.
+        let horizontal = match rows.cmp(&cols) {
+            std::cmp::Ordering::Greater => true,
+            std::cmp::Ordering::Less => false,
+            std::cmp::Ordering::Equal => rng.random(),
+        };
+        if horizontal {
+            let wall = row + 1 + 2 * rng.random_range(0..(rows - 1) / 2);
+            let gap = col + 2 * rng.random_range(0..cols.div_ceil(2));
+            let squares = (col..col + cols)
+                .filter(|&c| c != gap)
+                .map(|c| Position::new(wall as isize, c as isize))
+                .collect();
+            grid.set_positions(squares, Entity::Wall);
+            self.divide(grid, (row, col), (wall - row, cols), rng);
+            self.divide(grid, (wall + 1, col), (row + rows - wall - 1, cols), rng);
+        } else {
+            let wall = col + 1 + 2 * rng.random_range(0..(cols - 1) / 2);
+            let gap = row + 2 * rng.random_range(0..rows.div_ceil(2));
+            let squares = (row..row + rows)
+                .filter(|&r| r != gap)
+                .map(|r| Position::new(r as isize, wall as isize))
+                .collect();
+            grid.set_positions(squares, Entity::Wall);
+            self.divide(grid, (row, col), (rows, wall - col), rng);
+            self.divide(grid, (row, wall + 1), (rows, col + cols - wall - 1), rng);
+        }
+    }
+}
+
+/// Walls off the whole grid, then carves out between `min_rooms` and `max_rooms`
+/// rectangular rooms of up to `max_room_size` squares a side, joining each room
+/// to the previous one with an L-shaped corridor.
+pub struct RoomsAndCorridors {
+    pub min_rooms: usize,
+    pub max_rooms: usize,
+    pub max_room_size: usize,
+}
+
+impl WallStrategy for RoomsAndCorridors {
+    fn place_walls(&self, grid: &mut Grid, rng: &mut dyn RngCore) {
+        let positions = grid.all_positions();
+        grid.set_positions(positions, Entity::Wall);
+
+        let room_count = rng.random_range(self.min_rooms..=self.max_rooms);
+        let mut previous: Option<Position> = None;
+        for _ in 0..room_count {
+            let rows = rng.random_range(2..=self.max_room_size.min(grid.rows).max(2));
+            let cols = rng.random_range(2..=self.max_room_size.min(grid.cols).max(2));
+            let row = rng.random_range(0..=grid.rows.saturating_sub(rows));
+            let col = rng.random_range(0..=grid.cols.saturating_sub(cols));
+            for r in row..row + rows {
+                for c in col..col + cols {
+                    grid.clear(Position::new(r as isize, c as isize));
+                }
+            }
+
+            let centre = Position::new((row + rows / 2) as isize, (col + cols / 2) as isize);
+            if let Some(previous) = previous {
+                carve_corridor(grid, previous, centre, rng.random());
+            }
+            previous = Some(centre);
+        }
+    }
+}
+
+/// Clears an L-shaped corridor between two squares, going along the row first if
+/// `row_first`, otherwise along the column.
+fn carve_corridor(grid: &mut Grid, from: Position, to: Position, row_first: bool) {
+    for position in corridor(from, to, row_first) {
+        grid.clear(position);
+    }
+}
+
+/// Returns the squares of an L-shaped corridor between two squares, going along
+/// the row first if `row_first`, otherwise along the column.
+fn corridor(from: Position, to: Position, row_first: bool) -> Vec<Position> {
+    let corner = match row_first {
+        true => Position::new(from.row, to.col),
+        false => Position::new(to.row, from.col),
+    };
+    let mut squares = Vec::new();
+    for (start, end) in [(from, corner), (corner, to)] {
+        for row in start.row.min(end.row)..=start.row.max(end.row) {
+            for col in start.col.min(end.col)..=start.col.max(end.col) {
+                squares.push(Position::new(row, col));
+            }
+        }
+    }
+    squares
+}
+
+/// Places clumps of up to `max_blob_size` walls, each grown outwards from a
+/// random square, until between `min_area` and `max_area` of the grid is walled.
+pub struct Blobs {
+    pub min_area: f32,
+    pub max_area: f32,
+    pub max_blob_size: usize,
+}
+
+impl WallStrategy for Blobs {
+    fn place_walls(&self, grid: &mut Grid, rng: &mut dyn RngCore) {
+        let area = self.min_area + rng.random::<f32>() * (self.max_area - self.min_area);
+        let positions = grid.all_positions();
+        let wall_count = (area * positions.len() as f32).round() as usize;
+
+        let mut walls = 0;
+        while walls < wall_count {
+            let size = rng
+                .random_range(1..=self.max_blob_size)
+                .min(wall_count - walls);
+            let mut blob = vec![*positions.choose(rng).unwrap()];
+            while blob.len() < size {
+                let grown: Vec<Position> = blob
+                    .iter()
+                    .flat_map(|p| grid.valid_neighbours(*p))
+                    .filter(|p| !blob.contains(p))
+                    .collect();
+                match grown.choose(rng) {
+                    Some(position) => blob.push(*position),
+                    None => break,
+                }
+            }
+            for position in blob {
+                if !grid.is_wall(position) {
+                    grid.set_positions(vec![position], Entity::Wall);
+                    walls += 1;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::level_rng;
+
+    fn wall_fraction(grid: &Grid) -> f64 {
+        let positions = grid.all_positions();
+        let walls = positions.iter().filter(|p| grid.is_wall(**p)).count();
+        walls as f64 / positions.len() as f64
+    }
+
+    #[test]
+    fn mazes_are_connected() {
+        let maze = RecursiveDivision { min_size: 3 };
+        for (rows, cols) in [(8, 8), (9, 10), (5, 12), (15, 7)] {
+            for seed in 0..200 {
+                let mut grid = Grid::new(rows, cols);
+                maze.place_walls(&mut grid, &mut level_rng(seed));
+                assert!(wall_fraction(&grid) > 0.0);
+                assert_eq!(
+                    grid.find_connected_sections().len(),
+                    1,
+                    "{}x{} maze with seed {} is split",
+                    rows,
+                    cols,
+                    seed
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn noise_walls_off_the_chosen_area() {
+        let noise = UniformNoise {
+            min_area: 0.2,
+            max_area: 0.3,
+        };
+        for seed in 0..20 {
+            let mut grid = Grid::new(10, 10);
+            noise.place_walls(&mut grid, &mut level_rng(seed));
+            let fraction = wall_fraction(&grid);
+            assert!((0.2..=0.3).contains(&fraction), "{}", fraction);
+        }
+    }
+
+    #[test]
+    fn caves_follow_their_fill() {
+        let caves = |fill| CellularCaves { fill, steps: 2 };
+        let mut grid = Grid::new(10, 10);
+        caves(0.0).place_walls(&mut grid, &mut level_rng(0));
+        assert_eq!(wall_fraction(&grid), 0.0);
+
+        let mut grid = Grid::new(10, 10);
+        caves(1.0).place_walls(&mut grid, &mut level_rng(0));
+        // Only the corners, with three neighbours, are cleared.
+        assert_eq!(wall_fraction(&grid), 0.96);
+        assert!(!grid.is_wall(Position::new(0, 0)));
+
+        // Smoothing walls off squares surrounded by walls and clears lone ones,
+        // so the board stays a mix of both.
+        let mut total = 0.0;
+        for seed in 0..20 {
+            let mut grid = Grid::new(12, 12);
+            caves(0.5).place_walls(&mut grid, &mut level_rng(seed));
+            total += wall_fraction(&grid);
+        }
+        let average = total / 20.0;
+        assert!((0.2..=0.8).contains(&average), "{}", average);
+    }
+}
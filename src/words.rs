@@ -0,0 +1,382 @@
+use flate2::read::GzDecoder;
+use std::{
+    collections::HashMap,
+    fmt, fs,
+    io::{self, Read},
+    path::{Path, PathBuf},
+};
+
+use crate::language::Language;
+
+/// The ways loading a word list from disk can fail. Each carries the path of the
+/// file that failed, and where possible the line and the underlying cause.
+#[derive(Debug)]
+pub enum LoadWordsError {
+    FileError {
+        path: PathBuf,
+        source: io::Error,
+    },
+    DecompressError {
+        path: PathBuf,
+        source: io::Error,
+    },
+    EncodingError {
+        path: PathBuf,
+        line: usize,
+    },
+    ParseError {
+        path: PathBuf,
+        line: usize,
+        source: serde_json::Error,
+    },
+    NoWords {
+        path: PathBuf,
+    },
+    InvalidCount {
+        path: PathBuf,
+        line: usize,
+    },
+}
+
+impl fmt::Display for LoadWordsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LoadWordsError::FileError { path, source } => {
+                write!(f, "could not read {}: {}", path.display(), source)
+            }
+            LoadWordsError::DecompressError { path, source } => {
+                write!(f, "could not decompress {}: {}", path.display(), source)
+            }
+            LoadWordsError::EncodingError { path, line } => {
+                write!(f, "{}:{}: not valid UTF-8", path.display(), line)
+            }
+            LoadWordsError::ParseError { path, line, source } => {
+                write!(f, "{}:{}: invalid JSON: {}", path.display(), line, source)
+            }
+            LoadWordsError::NoWords { path } => {
+                write!(f, "{} does not contain any words", path.display())
+            }
+            LoadWordsError::InvalidCount { path, line } => {
+                write!(
+                    f,
+                    "{}:{}: count is not a whole number",
+                    path.display(),
+                    line
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for LoadWordsError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            LoadWordsError::FileError { source, .. } => Some(source),
+            LoadWordsError::DecompressError { source, .. } => Some(source),
+            LoadWordsError::ParseError { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+/// Where an entry in a word file was read from: a line of a text list, or the
+/// (1-based) index of an entry in a JSON list.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Location {
+    Line(usize),
+    Entry(usize),
+}
+
+impl fmt::Display for Location {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Location::Line(line) => write!(f, "line {}", line),
+            Location::Entry(entry) => write!(f, "entry {}", entry),
+        }
+    }
+}
+
+/// An entry of a word file that was left out of the word list.
+#[derive(Debug, Clone)]
+pub struct RejectedWord {
+    pub path: PathBuf,
+    pub location: Location,
+    pub word: String,
+}
+
+impl fmt::Display for RejectedWord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} ({}): {:?} contains letters outside the alphabet",
+            self.path.display(),
+            self.location,
+            self.word
+        )
+    }
+}
+
+/// The words read from one or more word files, after normalisation.
+#[derive(Debug, Default)]
+pub struct LoadedWords {
+    pub words: Vec<String>,
+    pub rejected: Vec<RejectedWord>,
+}
+
+/// Reads the words from each of the files, merging them together.
+pub fn read_word_files(
+    paths: &[impl AsRef<Path>],
+    language: Language,
+) -> Result<LoadedWords, LoadWordsError> {
+    let mut result = LoadedWords::default();
+    for path in paths {
+        let loaded = read_word_file(path.as_ref(), language)?;
+        result.words.extend(loaded.words);
+        result.rejected.extend(loaded.rejected);
+    }
+    Ok(result)
+}
+
+/// Reads the words from a file, which is either a JSON list of strings or a text
+/// file with a word on each line, optionally gzip compressed.
+///
+/// In text files, blank lines and lines starting with `#` are skipped. Every word
+/// is normalised for the language (see `Language::normalise`), and words with
+/// letters outside its alphabet are rejected.
+pub fn read_word_file(path: &Path, language: Language) -> Result<LoadedWords, LoadWordsError> {
+    let text = read_text(path)?;
+
+    let entries: Vec<(Location, String)> = if text.trim_start().starts_with('[') {
+        serde_json::from_str::<Vec<String>>(&text)
+            .map_err(|source| LoadWordsError::ParseError {
+                path: path.to_path_buf(),
+                line: source.line(),
+                source,
+            })?
+            .into_iter()
+            .enumerate()
+            .map(|(i, word)| (Location::Entry(i + 1), word))
+            .collect()
+    } else {
+        text.lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim_start().starts_with('#'))
+            .map(|(i, line)| (Location::Line(i + 1), line.to_string()))
+            .collect()
+    };
+
+    let mut result = LoadedWords::default();
+    for (location, entry) in entries {
+        if entry.trim().is_empty() {
+            continue;
+        }
+        match language.normalise(&entry) {
+            Some(word) => result.words.push(word),
+            None => result.rejected.push(RejectedWord {
+                path: path.to_path_buf(),
+                location,
+                word: entry,
+            }),
+        }
+    }
+
+    if result.words.is_empty() {
+        return Err(LoadWordsError::NoWords {
+            path: path.to_path_buf(),
+        });
+    }
+    Ok(result)
+}
+
+/// Reads how common each word is from a file with a word on each line, followed by
+/// a tab and the number of times it was seen, optionally gzip compressed. Files
+/// without counts are taken to be in order from most to least common.
+///
+/// Returns the rank of each word, where the most common word has rank 1. Lines
+/// starting with `#`, and words with letters outside the language's alphabet, are
+/// skipped.
+pub fn read_frequency_file(
+    path: &Path,
+    language: Language,
+) -> Result<HashMap<String, usize>, LoadWordsError> {
+    let text = read_text(path)?;
+
+    let mut counts: Vec<(u64, usize, String)> = Vec::new();
+    for (i, line) in text.lines().enumerate() {
+        if line.trim_start().starts_with('#') {
+            continue;
+        }
+        let mut columns = line.split('\t');
+        let Some(word) = language.normalise(columns.next().unwrap_or_default()) else {
+            continue;
+        };
+        if word.is_empty() {
+            continue;
+        }
+        let count = match columns.next() {
+            Some(count) => count
+                .trim()
+                .parse()
+                .map_err(|_| LoadWordsError::InvalidCount {
+                    path: path.to_path_buf(),
+                    line: i + 1,
+                })?,
+            None => 0,
+        };
+        counts.push((count, i, word));
+    }
+    if counts.is_empty() {
+        return Err(LoadWordsError::NoWords {
+            path: path.to_path_buf(),
+        });
+    }
+
+    // Most common first, keeping the file's order for ties.
+    counts.sort_by(|a, b| b.0.cmp(&a.0).then(a.1.cmp(&b.1)));
+    let mut ranks = HashMap::new();
+    for (rank, (_, _, word)) in counts.into_iter().enumerate() {
+        ranks.entry(word).or_insert(rank + 1);
+    }
+    Ok(ranks)
+}
+
+/// Reads a text file, decompressing it if it is gzip compressed.
+fn read_text(path: &Path) -> Result<String, LoadWordsError> {
+    let raw = fs::read(path).map_err(|source| LoadWordsError::FileError {
+        path: path.to_path_buf(),
+        source,
+    })?;
+
+    // Detect gzip by its magic bytes rather than the extension.
+    let raw = if raw.starts_with(&[0x1f, 0x8b]) {
+        let mut decompressed = Vec::new();
+        GzDecoder::new(raw.as_slice())
+            .read_to_end(&mut decompressed)
+            .map_err(|source| LoadWordsError::DecompressError {
+                path: path.to_path_buf(),
+                source,
+            })?;
+        decompressed
+    } else {
+        raw
+    };
+
+    String::from_utf8(raw).map_err(|e| LoadWordsError::EncodingError {
+        path: path.to_path_buf(),
+        line: line_of(e.as_bytes(), e.utf8_error().valid_up_to()),
+    })
+}
+
+/// Returns the 1-based line number of the byte offset.
+fn line_of(bytes: &[u8], offset: usize) -> usize {
+    bytes[..offset].iter().filter(|&&b| b == b'\n').count() + 1
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::{Compression, write::GzEncoder};
+    use std::io::Write;
+
+    /// A file in the temporary directory, removed when dropped.
+    struct TempFile(PathBuf);
+
+    impl TempFile {
+        fn new(name: &str, contents: &[u8]) -> Self {
+            let path = std::env::temp_dir().join(format!("wc-rs-{}-{}", std::process::id(), name));
+            fs::write(&path, contents).unwrap();
+            TempFile(path)
+        }
+    }
+
+    impl Drop for TempFile {
+        fn drop(&mut self) {
+            let _ = fs::remove_file(&self.0);
+        }
+    }
+
+    fn gzip(contents: &[u8]) -> Vec<u8> {
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(contents).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    #[test]
+    fn reads_text_lists() {
+        let file = TempFile::new("list.txt", b"# A comment\nCat\n\n  dog \nx-ray\n");
+        let loaded = read_word_file(&file.0, Language::English).unwrap();
+        assert_eq!(loaded.words, vec!["cat", "dog"]);
+        assert_eq!(loaded.rejected.len(), 1);
+        assert_eq!(loaded.rejected[0].location, Location::Line(5));
+        assert_eq!(loaded.rejected[0].word, "x-ray");
+    }
+
+    #[test]
+    fn reads_json_lists() {
+        let file = TempFile::new("list.json", b"  [\"cat\", \"x-ray\", \"\", \"dog\"]");
+        let loaded = read_word_file(&file.0, Language::English).unwrap();
+        assert_eq!(loaded.words, vec!["cat", "dog"]);
+        assert_eq!(loaded.rejected.len(), 1);
+        assert_eq!(loaded.rejected[0].location, Location::Entry(2));
+    }
+
+    #[test]
+    fn decompresses_gzip_whatever_the_extension() {
+        let text = TempFile::new("gzip.txt", &gzip(b"cat\ndog\n"));
+        let json = TempFile::new("gzip.json", &gzip(b"[\"cat\", \"dog\"]"));
+        for file in [text, json] {
+            let loaded = read_word_file(&file.0, Language::English).unwrap();
+            assert_eq!(loaded.words, vec!["cat", "dog"]);
+        }
+
+        let truncated = gzip(b"cat\ndog\n");
+        let file = TempFile::new("truncated.gz", &truncated[..truncated.len() / 2]);
+        assert!(matches!(
+            read_word_file(&file.0, Language::English),
+            Err(LoadWordsError::DecompressError { .. })
+        ));
+    }
+
+    #[test]
+    fn reports_the_line_of_errors() {
+        let file = TempFile::new("broken.json", b"[\n  \"cat\",\n  \"dog\"\n  \"hen\"\n]");
+        assert!(matches!(
+            read_word_file(&file.0, Language::English),
+            Err(LoadWordsError::ParseError { line: 4, .. })
+        ));
+
+        let file = TempFile::new("latin1.txt", b"cat\ndog\ncaf\xe9\n");
+        assert!(matches!(
+            read_word_file(&file.0, Language::English),
+            Err(LoadWordsError::EncodingError { line: 3, .. })
+        ));
+
+        let file = TempFile::new("empty.txt", b"# Nothing here\n\n");
+        assert!(matches!(
+            read_word_file(&file.0, Language::English),
+            Err(LoadWordsError::NoWords { .. })
+        ));
+    }
+
+    #[test]
+    fn ranks_words_by_count() {
+        let file = TempFile::new(
+            "ranks.txt",
+            b"# word\tcount\ncat\t5\ndog\t20\nhen\t5\nCat\t1\n",
+        );
+        let ranks = read_frequency_file(&file.0, Language::English).unwrap();
+        assert_eq!(ranks["dog"], 1);
+        assert_eq!(ranks["cat"], 2);
+        assert_eq!(ranks["hen"], 3);
+
+        let file = TempFile::new("ordered.txt", b"the\nand\n");
+        let ranks = read_frequency_file(&file.0, Language::English).unwrap();
+        assert_eq!((ranks["the"], ranks["and"]), (1, 2));
+
+        let file = TempFile::new("counts.txt", b"cat\t10\ndog\tmany\n");
+        assert!(matches!(
+            read_frequency_file(&file.0, Language::English),
+            Err(LoadWordsError::InvalidCount { line: 2, .. })
+        ));
+    }
+}